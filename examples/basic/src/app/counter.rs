@@ -1,4 +1,4 @@
-use rust_elm_architecture::{Attribute, Commands, Html};
+use rust_elm_architecture::{html, window::KeyDown, Commands, Html, Subs, Subscription};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Model {
@@ -24,22 +24,21 @@ pub fn update(message: Message, model: &mut Model) -> Commands<Message> {
     vec![]
 }
 
+pub fn subscriptions(_model: &Model) -> Subs<Message> {
+    vec![KeyDown::new(|key| match key {
+        "ArrowUp" => Some(Message::Increment),
+        "ArrowDown" => Some(Message::Decrement),
+        _ => None,
+    })
+    .boxed()]
+}
+
 pub fn view(model: &Model) -> Html<Message> {
-    Html::div(
-        vec![],
-        vec![
-            Html::button(
-                vec![Attribute::on_click(Message::Decrement)],
-                vec![Html::text("-")],
-            ),
-            Html::span(
-                vec![Attribute::class("counter")],
-                vec![Html::text(&model.counter.to_string())],
-            ),
-            Html::button(
-                vec![Attribute::on_click(Message::Increment)],
-                vec![Html::text("+")],
-            ),
-        ],
-    )
+    html! {
+        <div>
+            <button onclick={Message::Decrement}>"-"</button>
+            <span class="counter">{model.counter.to_string()}</span>
+            <button onclick={Message::Increment}>"+"</button>
+        </div>
+    }
 }
@@ -74,16 +74,23 @@ pub fn view(model: &Model) -> Html<Message> {
 }
 
 fn pull_requests_view(data_loading: &DataLoading<Vec<PullRequest>>) -> Html<Message> {
-    Html::div(
-        vec![],
-        match data_loading {
-            DataLoading::Loading => vec![Html::text("Loading...")],
-            DataLoading::Error => vec![Html::text("Error")],
-            DataLoading::Loaded(pull_requests) => {
-                pull_requests.iter().map(pull_request_view).collect()
-            }
-        },
-    )
+    match data_loading {
+        DataLoading::Loading => Html::div(vec![], vec![Html::text("Loading...")]),
+        DataLoading::Error => Html::div(vec![], vec![Html::text("Error")]),
+        DataLoading::Loaded(pull_requests) => Html::keyed(
+            "div",
+            vec![],
+            pull_requests
+                .iter()
+                .map(|pull_request| {
+                    (
+                        pull_request.number.to_string(),
+                        pull_request_view(pull_request),
+                    )
+                })
+                .collect(),
+        ),
+    }
 }
 
 fn pull_request_view(pull_request: &PullRequest) -> Html<Message> {
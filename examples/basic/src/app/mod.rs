@@ -4,7 +4,7 @@ mod github;
 
 pub use data_loading::DataLoading;
 
-use rust_elm_architecture::{command, App, Commands, Html};
+use rust_elm_architecture::{command, subscription, App, Commands, Html, Subs};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Model {
@@ -55,16 +55,23 @@ fn update(message: Message, model: &mut Model) -> Commands<Message> {
     }
 }
 
+fn subscriptions(model: &Model) -> Subs<Message> {
+    counter::subscriptions(&model.counter)
+        .into_iter()
+        .map(|subscription| subscription::map(subscription, Message::Counter))
+        .collect()
+}
+
 fn view(model: &Model) -> Html<Message> {
     Html::div(
         vec![],
         vec![
-            counter::view(&model.counter).map(Message::Counter),
+            Html::lazy(counter::view, model.counter.clone()).map(Message::Counter),
             github::view(&model.github).map(Message::Github),
         ],
     )
 }
 
 pub fn create(root_id: &str) -> App<Model, Message> {
-    App::new(init, update, view, root_id)
+    App::new(init, update, view, root_id).subscriptions(subscriptions)
 }
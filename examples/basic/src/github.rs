@@ -15,5 +15,5 @@ where
     Handler: 'static + Fn(Result<Vec<PullRequest>, JsValue>) -> Message,
 {
     let url = format!("https://api.github.com/repos/{}/pulls?state=all", repo);
-    Fetch::new(&url, move |result| handler(result)).boxed()
+    Fetch::new(&url, handler).boxed()
 }
@@ -0,0 +1,433 @@
+use crate::spec::{self, Kind};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    braced,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    Error, Expr, Ident, LitStr, Result, Token,
+};
+
+/// The markup passed to `html!`: a single node.
+pub struct Html(pub(crate) Node);
+
+pub(crate) enum Node {
+    Element(Element),
+    Text(LitStr),
+    Expr(Expr),
+    // `{for iterator}`
+    Iter(Expr),
+    If(If),
+}
+
+pub(crate) struct Element {
+    pub(crate) name: Name,
+    pub(crate) attributes: Vec<Attribute>,
+    pub(crate) children: Vec<Node>,
+}
+
+// A tag or attribute name, which can contain `-` and `:`.
+pub(crate) struct Name {
+    pub(crate) value: String,
+    pub(crate) span: Span,
+}
+
+pub(crate) struct Attribute {
+    pub(crate) name: Name,
+    pub(crate) value: Value,
+}
+
+pub(crate) enum Value {
+    None,
+    Lit(LitStr),
+    Expr(Box<Expr>),
+}
+
+pub(crate) struct If {
+    condition: Expr,
+    then: Vec<Node>,
+    otherwise: Option<Else>,
+}
+
+enum Else {
+    If(Box<If>),
+    Block(Vec<Node>),
+}
+
+impl Parse for Html {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let node: Node = input.parse()?;
+
+        if let Node::Iter(_) | Node::If(_) = node {
+            return Err(input.error("must be a single element, text or `{expression}`"));
+        }
+
+        if !input.is_empty() {
+            return Err(input.error("must have a single root node"));
+        }
+
+        Ok(Html(node))
+    }
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![<]) {
+            Ok(Node::Element(input.parse()?))
+        } else if input.peek(LitStr) {
+            Ok(Node::Text(input.parse()?))
+        } else if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+
+            if content.peek(Token![for]) {
+                content.parse::<Token![for]>()?;
+                Ok(Node::Iter(content.parse()?))
+            } else {
+                Ok(Node::Expr(content.parse()?))
+            }
+        } else if input.peek(Token![if]) {
+            Ok(Node::If(input.parse()?))
+        } else {
+            Err(input.error("expected an element, \"text\", `{expression}` or `if`"))
+        }
+    }
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![<]>()?;
+        let name: Name = input.parse()?;
+
+        let mut attributes = vec![];
+        while !input.peek(Token![>]) && !input.peek(Token![/]) {
+            attributes.push(input.parse()?);
+        }
+
+        if input.peek(Token![/]) {
+            input.parse::<Token![/]>()?;
+            input.parse::<Token![>]>()?;
+
+            return Ok(Element {
+                name,
+                attributes,
+                children: vec![],
+            });
+        }
+
+        input.parse::<Token![>]>()?;
+
+        let mut children = vec![];
+        while !(input.peek(Token![<]) && input.peek2(Token![/])) {
+            if input.is_empty() {
+                return Err(Error::new(
+                    name.span,
+                    format!("`<{}>` is never closed", name.value),
+                ));
+            }
+
+            children.push(input.parse()?);
+        }
+
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![/]>()?;
+        let end_name: Name = input.parse()?;
+        if end_name.value != name.value {
+            return Err(Error::new(
+                end_name.span,
+                format!("expected `</{}>`", name.value),
+            ));
+        }
+        input.parse::<Token![>]>()?;
+
+        Ok(Element {
+            name,
+            attributes,
+            children,
+        })
+    }
+}
+
+impl Parse for Name {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let first = Ident::parse_any(input)?;
+        let mut value = first.to_string();
+
+        loop {
+            if input.peek(Token![-]) {
+                input.parse::<Token![-]>()?;
+                value.push('-');
+            } else if input.peek(Token![:]) {
+                input.parse::<Token![:]>()?;
+                value.push(':');
+            } else {
+                break;
+            }
+
+            value.push_str(&Ident::parse_any(input)?.to_string());
+        }
+
+        Ok(Name {
+            value,
+            span: first.span(),
+        })
+    }
+}
+
+impl Parse for Attribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+
+        if !input.peek(Token![=]) {
+            return Ok(Attribute {
+                name,
+                value: Value::None,
+            });
+        }
+
+        input.parse::<Token![=]>()?;
+
+        let value = if input.peek(LitStr) {
+            Value::Lit(input.parse()?)
+        } else if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            Value::Expr(Box::new(content.parse()?))
+        } else {
+            return Err(input.error("expected \"text\" or `{expression}`"));
+        };
+
+        Ok(Attribute { name, value })
+    }
+}
+
+impl Parse for If {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![if]>()?;
+        let condition = Expr::parse_without_eager_brace(input)?;
+        let then = parse_block(input)?;
+
+        let otherwise = if input.peek(Token![else]) {
+            input.parse::<Token![else]>()?;
+
+            if input.peek(Token![if]) {
+                Some(Else::If(Box::new(input.parse()?)))
+            } else {
+                Some(Else::Block(parse_block(input)?))
+            }
+        } else {
+            None
+        };
+
+        Ok(If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+}
+
+fn parse_block(input: ParseStream) -> Result<Vec<Node>> {
+    let content;
+    braced!(content in input);
+
+    let mut nodes = vec![];
+    while !content.is_empty() {
+        nodes.push(content.parse()?);
+    }
+
+    Ok(nodes)
+}
+
+impl Html {
+    pub fn expand(&self) -> Result<TokenStream> {
+        match &self.0 {
+            Node::Element(element) => element.expand(),
+            Node::Text(text) => Ok(quote! { ::rust_elm_architecture::Html::text(#text) }),
+            Node::Expr(expr) => Ok(quote! { ::core::convert::Into::into(#expr) }),
+            Node::Iter(_) | Node::If(_) => unreachable!(),
+        }
+    }
+}
+
+impl Element {
+    pub(crate) fn expand(&self) -> Result<TokenStream> {
+        let name = &self.name.value;
+        let attributes = self
+            .attributes
+            .iter()
+            .map(Attribute::expand)
+            .collect::<Result<Vec<_>>>()?;
+        let children = expand_children(&self.children)?;
+
+        if spec::is_element(name) {
+            let function = Ident::new(name, self.name.span);
+
+            Ok(quote! {
+                ::rust_elm_architecture::Html::#function(::std::vec![#(#attributes),*], #children)
+            })
+        } else if name.contains('-') {
+            // https://html.spec.whatwg.org/multipage/custom-elements.html#valid-custom-element-name
+            Ok(quote! {
+                ::rust_elm_architecture::Html::Element(
+                    ::rust_elm_architecture::virtual_dom::Element::new(
+                        #name,
+                        ::std::vec![#(#attributes),*],
+                        ::rust_elm_architecture::virtual_dom::Children::Unkeyed(#children),
+                    )
+                )
+            })
+        } else {
+            Err(Error::new(
+                self.name.span,
+                format!("unknown element `<{}>`", name),
+            ))
+        }
+    }
+}
+
+impl Attribute {
+    fn expand(&self) -> Result<TokenStream> {
+        let name = self.name.value.as_str();
+        let span = self.name.span;
+
+        let event = |function: &str| {
+            let function = Ident::new(function, span);
+            let expr = self.expr()?;
+            Ok(quote_spanned! {span=> ::rust_elm_architecture::Attribute::#function(#expr) })
+        };
+
+        match name {
+            "onclick" => return event("on_click"),
+            "oninput" => return event("on_input"),
+            "onsubmit" => return event("on_submit"),
+            _ => {}
+        }
+
+        if let Some(event_name) = name.strip_prefix("on:") {
+            let decoder = self.expr()?;
+
+            return Ok(quote_spanned! {span=>
+                ::rust_elm_architecture::Attribute::on(#event_name, #decoder)
+            });
+        }
+
+        if name.starts_with("data-") || name.starts_with("aria-") {
+            let value = self.text()?;
+
+            return Ok(quote_spanned! {span=>
+                ::rust_elm_architecture::Attribute::Text(#name.into(), #value.into())
+            });
+        }
+
+        let (function, kind) = match spec::attribute(name) {
+            Some(attribute) => attribute,
+            None if name.starts_with("on") => {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "unknown event attribute `{}`, use `on:{}={{decoder}}` for other events",
+                        name,
+                        &name[2..]
+                    ),
+                ))
+            }
+            None => return Err(Error::new(span, format!("unknown attribute `{}`", name))),
+        };
+
+        let function = Ident::new(function, span);
+        let value = match kind {
+            Kind::Text => self.text()?,
+            Kind::Bool => match &self.value {
+                Value::None => quote! { true },
+                Value::Expr(expr) => quote! { #expr },
+                Value::Lit(lit) => {
+                    return Err(Error::new(
+                        lit.span(),
+                        format!("`{}` takes a `{{bool}}`", name),
+                    ))
+                }
+            },
+        };
+
+        Ok(quote_spanned! {span=> ::rust_elm_architecture::Attribute::#function(#value) })
+    }
+
+    fn expr(&self) -> Result<&Expr> {
+        match &self.value {
+            Value::Expr(expr) => Ok(&**expr),
+            _ => Err(Error::new(
+                self.name.span,
+                format!("`{}` takes an `{{expression}}`", self.name.value),
+            )),
+        }
+    }
+
+    // The value as a `&str`.
+    fn text(&self) -> Result<TokenStream> {
+        Ok(match &self.value {
+            Value::None => quote! { "" },
+            Value::Lit(lit) => quote! { #lit },
+            Value::Expr(expr) => quote! { ::core::convert::AsRef::<str>::as_ref(&(#expr)) },
+        })
+    }
+}
+
+fn expand_children(children: &[Node]) -> Result<TokenStream> {
+    // not visible to the expressions in the markup
+    let vec = Ident::new("children", Span::mixed_site());
+    let pushes = expand_pushes(children, &vec)?;
+
+    Ok(quote! {
+        {
+            #[allow(unused_mut)]
+            let mut #vec = ::std::vec::Vec::new();
+            #pushes
+            #vec
+        }
+    })
+}
+
+fn expand_pushes(children: &[Node], vec: &Ident) -> Result<TokenStream> {
+    children
+        .iter()
+        .map(|child| {
+            Ok(match child {
+                Node::Element(element) => {
+                    let element = element.expand()?;
+                    quote! { #vec.push(#element); }
+                }
+                Node::Text(text) => quote! {
+                    #vec.push(::rust_elm_architecture::Html::text(#text));
+                },
+                Node::Expr(expr) => quote! {
+                    #vec.push(::core::convert::Into::into(#expr));
+                },
+                Node::Iter(expr) => quote! {
+                    #vec.extend(
+                        ::core::iter::IntoIterator::into_iter(#expr).map(::core::convert::Into::into)
+                    );
+                },
+                Node::If(if_) => expand_if(if_, vec)?,
+            })
+        })
+        .collect()
+}
+
+fn expand_if(if_: &If, vec: &Ident) -> Result<TokenStream> {
+    let condition = &if_.condition;
+    let then = expand_pushes(&if_.then, vec)?;
+    let otherwise = match &if_.otherwise {
+        Some(Else::If(if_)) => {
+            let if_ = expand_if(if_, vec)?;
+            quote! { else #if_ }
+        }
+        Some(Else::Block(nodes)) => {
+            let nodes = expand_pushes(nodes, vec)?;
+            quote! { else { #nodes } }
+        }
+        None => quote! {},
+    };
+
+    Ok(quote! { if #condition { #then } #otherwise })
+}
@@ -1,9 +1,13 @@
 extern crate proc_macro;
 
+mod html;
+mod spec;
+mod template;
+mod typed;
 mod utils;
 
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
+use proc_macro2::{Ident, Span};
 use quote::quote;
 use syn::{parse::Parser, punctuated::Punctuated, Expr, Token};
 
@@ -14,6 +18,8 @@ where
     f().unwrap_or_else(|e| e).into()
 }
 
+/// Generates the `Node` constructor of a single element, named by a string
+/// literal.
 #[proc_macro]
 pub fn element(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as Expr);
@@ -21,43 +27,160 @@ pub fn element(item: TokenStream) -> TokenStream {
     from_result(move || {
         let value = utils::str_literal(&input)?;
 
-        let function_name = Ident::new(&value.value(), value.span());
-        let node_name = value.value();
-
-        let result = quote! {
-          pub fn #function_name(attributes: Vec<Attribute<Message>>, children: Vec<Node<Message>>) -> Node<Message> {
-            Node::Element(Element {
-              name: #node_name.into(),
-              attributes,
-              children,
-            })
-          }
-        };
-
-        Ok(result)
+        Ok(element_function(&value.value(), value.span()))
     })
 }
 
+/// Generates the `Node` constructor of every element in the element table,
+/// which `html!` checks tag names against.
 #[proc_macro]
-pub fn attribute(items: TokenStream) -> TokenStream {
-    attribute_macro(items, |function_name, attribute_name| {
-        quote! {
-            pub fn #function_name(value: &str) -> Self {
-                Self::Text(#attribute_name.into(), value.into())
+pub fn elements(item: TokenStream) -> TokenStream {
+    syn::parse_macro_input!(item as syn::parse::Nothing);
+
+    let functions = spec::ELEMENTS
+        .iter()
+        .map(|name| element_function(name, Span::call_site()));
+
+    quote! { #(#functions)* }.into()
+}
+
+fn element_function(node_name: &str, span: Span) -> proc_macro2::TokenStream {
+    let function_name = Ident::new(node_name, span);
+
+    quote! {
+      pub fn #function_name(attributes: Vec<Attribute<Message>>, children: Vec<Node<Message>>) -> Node<Message> {
+        Node::Element(Element::new(#node_name, attributes, Children::Unkeyed(children)))
+      }
+    }
+}
+
+/// Builds an `Html` value from JSX-like markup, expanding to the `Html` and
+/// `Attribute` constructors.
+///
+/// Attribute values are `"text"` or `{expression}`, and boolean attributes
+/// can be written without a value. `onclick`, `oninput` and `onsubmit` take
+/// what `Attribute::on_click`, `on_input` and `on_submit` do, and `on:name`
+/// takes a decoder for any other event. Children are elements, `"text"`,
+/// `{expression}`s that convert into `Html`, `{for iterator}`s of them, and
+/// `if` / `else` blocks of children.
+///
+/// ```ignore
+/// html! {
+///     <div class="counter">
+///         <button onclick={Message::Decrement}>"-"</button>
+///         {model.count.to_string()}
+///         if model.count > 10 { <strong>"That's a lot"</strong> }
+///         <ul>{for model.items.iter().map(|item| html! { <li>{item.as_str()}</li> })}</ul>
+///     </div>
+/// }
+/// ```
+#[proc_macro]
+pub fn html(item: TokenStream) -> TokenStream {
+    let html = syn::parse_macro_input!(item as html::Html);
+
+    from_result(move || html.expand().map_err(|error| error.to_compile_error()))
+}
+
+/// Builds an `Html` value from an HTML file, such as a snippet from a
+/// designer, which is read and checked while compiling. The path is relative
+/// to the crate's `Cargo.toml`, and the crate is rebuilt when the file
+/// changes.
+///
+/// The file has a single root element. `{expression}` holes in text and
+/// attribute values are Rust expressions, evaluated where the macro is used:
+/// in text they convert into `Html`, and in attribute values they are
+/// formatted into the text around them. `{{` and `}}` are literal braces.
+/// Event attributes (`onclick`, `oninput`, `onsubmit` and `on:name`) name a
+/// binding passed to the macro, which is what the `html!` attribute of the
+/// same name takes.
+///
+/// Malformed markup, unknown elements or attributes, and missing or unused
+/// bindings fail the build with the line and column in the file.
+///
+/// ```ignore
+/// // templates/counter.html:
+/// // <div class="counter {model.size}">
+/// //   <button onclick="decrement">-</button>
+/// //   <span>{model.count.to_string()}</span>
+/// // </div>
+/// include_html!("templates/counter.html", decrement = Message::Decrement)
+/// ```
+#[proc_macro]
+pub fn include_html(item: TokenStream) -> TokenStream {
+    let template = syn::parse_macro_input!(item as template::Template);
+
+    from_result(move || template.expand().map_err(|error| error.to_compile_error()))
+}
+
+/// Generates the element types, their constructors and the `has` traits of
+/// the typed layer, from the attribute tables.
+#[proc_macro]
+pub fn typed_elements(item: TokenStream) -> TokenStream {
+    syn::parse_macro_input!(item as syn::parse::Nothing);
+
+    typed::elements().into()
+}
+
+/// Generates the typed attribute constructors, from the attribute tables.
+#[proc_macro]
+pub fn typed_attributes(item: TokenStream) -> TokenStream {
+    syn::parse_macro_input!(item as syn::parse::Nothing);
+
+    typed::attributes().into()
+}
+
+/// Generates the `Attribute` constructor of every attribute in the attribute
+/// table but the properties, which `html!` checks attribute names against.
+#[proc_macro]
+pub fn attributes(item: TokenStream) -> TokenStream {
+    syn::parse_macro_input!(item as syn::parse::Nothing);
+
+    let functions = spec::ATTRIBUTES
+        .iter()
+        .filter(|(name, _, _)| !spec::PROPERTIES.contains(name))
+        .map(|(name, function, kind)| {
+            let function_name = Ident::new(function, Span::call_site());
+            let attribute_name = name.to_string();
+
+            match kind {
+                spec::Kind::Text => text_attribute_function(&function_name, &attribute_name),
+                spec::Kind::Bool => bool_attribute_function(&function_name, &attribute_name),
             }
-        }
-    })
+        });
+
+    quote! { #(#functions)* }.into()
+}
+
+#[proc_macro]
+pub fn attribute(items: TokenStream) -> TokenStream {
+    attribute_macro(items, text_attribute_function)
 }
 
 #[proc_macro]
 pub fn bool_attribute(items: TokenStream) -> TokenStream {
-    attribute_macro(items, |function_name, attribute_name| {
-        quote! {
-            pub fn #function_name(value: bool) -> Self {
-                Self::Bool(#attribute_name.into(), value)
-            }
+    attribute_macro(items, bool_attribute_function)
+}
+
+fn text_attribute_function(
+    function_name: &Ident,
+    attribute_name: &String,
+) -> proc_macro2::TokenStream {
+    quote! {
+        pub fn #function_name(value: &str) -> Self {
+            Self::Text(#attribute_name.into(), value.into())
         }
-    })
+    }
+}
+
+fn bool_attribute_function(
+    function_name: &Ident,
+    attribute_name: &String,
+) -> proc_macro2::TokenStream {
+    quote! {
+        pub fn #function_name(value: bool) -> Self {
+            Self::Bool(#attribute_name.into(), value)
+        }
+    }
 }
 
 fn attribute_macro<FunctionGenerator>(
@@ -0,0 +1,564 @@
+//! The elements and attributes that have constructors on `Node` and
+//! `Attribute`, which the `html!` macro checks names against, and which
+//! attributes each element has, which the typed constructors are generated
+//! from.
+
+/// The type of value an attribute constructor takes.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Text,
+    Bool,
+}
+
+/// Tag names, which are also the names of the constructors `elements!`
+/// generates.
+pub const ELEMENTS: &[&str] = &[
+    // From https://developer.mozilla.org/en-US/docs/Web/HTML/Element
+
+    // Content Sectioning
+    "address",
+    "article",
+    "aside",
+    "footer",
+    "header",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "main",
+    "nav",
+    "section",
+    // Text Content
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "ul",
+    // Inline Text Semantics
+    "a",
+    "abbr",
+    "b",
+    "bdi",
+    "bdo",
+    "br",
+    "cite",
+    "code",
+    "data",
+    "dfn",
+    "em",
+    "i",
+    "kbd",
+    "mark",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "time",
+    "u",
+    "var",
+    "wbr",
+    // Image and Multimedia
+    "area",
+    "audio",
+    "img",
+    // no `map`, which would clash with `Node::map`
+    "track",
+    "video",
+    // Embedded Content
+    "embed",
+    "iframe",
+    "object",
+    "param",
+    "picture",
+    "portal",
+    "source",
+    // SVG and MathML
+    "svg",
+    "math",
+    // Scripting
+    "canvas",
+    "noscript",
+    "script",
+    // Demarcating Edits
+    "del",
+    "ins",
+    // Table Content
+    "caption",
+    "col",
+    "colgroup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    // Forms
+    "button",
+    "datalist",
+    "fieldset",
+    "form",
+    "input",
+    "label",
+    "legend",
+    "meter",
+    "optgroup",
+    "option",
+    "output",
+    "progress",
+    "select",
+    "textarea",
+    // Interactive Elements
+    "details",
+    "dialog",
+    "menu",
+    "summary",
+    // Web Components
+    "slot",
+    "template",
+];
+
+/// Attribute names, with the name of their constructor and its kind.
+pub const ATTRIBUTES: &[(&str, &str, Kind)] = &[
+    ("accept", "accept", Kind::Text),
+    ("accept-charset", "accept_charset", Kind::Text),
+    ("accesskey", "accesskey", Kind::Text),
+    ("action", "action", Kind::Text),
+    ("align", "align", Kind::Text),
+    ("allow", "allow", Kind::Text),
+    ("alt", "alt", Kind::Text),
+    ("async", "async_", Kind::Text),
+    ("autocapitalize", "autocapitalize", Kind::Text),
+    ("autocomplete", "autocomplete", Kind::Text),
+    ("autofocus", "autofocus", Kind::Text),
+    ("autoplay", "autoplay", Kind::Text),
+    ("buffered", "buffered", Kind::Text),
+    ("capture", "capture", Kind::Text),
+    ("challenge", "challenge", Kind::Text),
+    ("charset", "charset", Kind::Text),
+    ("checked", "checked", Kind::Bool),
+    ("cite", "cite", Kind::Text),
+    ("class", "class", Kind::Text),
+    ("code", "code", Kind::Text),
+    ("codebase", "codebase", Kind::Text),
+    ("color", "color", Kind::Text),
+    ("cols", "cols", Kind::Text),
+    ("colspan", "colspan", Kind::Text),
+    ("content", "content", Kind::Text),
+    ("contenteditable", "contenteditable", Kind::Text),
+    ("contextmenu", "contextmenu", Kind::Text),
+    ("controls", "controls", Kind::Text),
+    ("coords", "coords", Kind::Text),
+    ("crossorigin", "crossorigin", Kind::Text),
+    ("csp", "csp", Kind::Text),
+    ("data", "data", Kind::Text),
+    ("datetime", "datetime", Kind::Text),
+    ("decoding", "decoding", Kind::Text),
+    ("default", "default", Kind::Text),
+    ("defer", "defer", Kind::Text),
+    ("dir", "dir", Kind::Text),
+    ("dirname", "dirname", Kind::Text),
+    ("disabled", "disabled", Kind::Bool),
+    ("download", "download", Kind::Text),
+    ("draggable", "draggable", Kind::Text),
+    ("enctype", "enctype", Kind::Text),
+    ("enterkeyhint", "enterkeyhint", Kind::Text),
+    ("for", "for_", Kind::Text),
+    ("form", "form", Kind::Text),
+    ("formaction", "formaction", Kind::Text),
+    ("formenctype", "formenctype", Kind::Text),
+    ("formmethod", "formmethod", Kind::Text),
+    ("formnovalidate", "formnovalidate", Kind::Bool),
+    ("formtarget", "formtarget", Kind::Text),
+    ("headers", "headers", Kind::Text),
+    ("height", "height", Kind::Text),
+    ("hidden", "hidden", Kind::Text),
+    ("high", "high", Kind::Text),
+    ("href", "href", Kind::Text),
+    ("hreflang", "hreflang", Kind::Text),
+    ("icon", "icon", Kind::Text),
+    ("id", "id", Kind::Text),
+    ("importance", "importance", Kind::Text),
+    ("inputmode", "inputmode", Kind::Text),
+    ("integrity", "integrity", Kind::Text),
+    ("intrinsicsize", "intrinsicsize", Kind::Text),
+    ("ismap", "ismap", Kind::Text),
+    ("itemprop", "itemprop", Kind::Text),
+    ("keytype", "keytype", Kind::Text),
+    ("kind", "kind", Kind::Text),
+    ("label", "label", Kind::Text),
+    ("lang", "lang", Kind::Text),
+    ("language", "language", Kind::Text),
+    ("list", "list", Kind::Text),
+    ("loading", "loading", Kind::Text),
+    ("loop", "loop_", Kind::Text),
+    ("low", "low", Kind::Text),
+    ("manifest", "manifest", Kind::Text),
+    ("map", "map_", Kind::Text),
+    ("max", "max", Kind::Text),
+    ("maxlength", "maxlength", Kind::Text),
+    ("media", "media", Kind::Text),
+    ("method", "method", Kind::Text),
+    ("min", "min", Kind::Text),
+    ("minlength", "minlength", Kind::Text),
+    ("multiple", "multiple", Kind::Text),
+    ("muted", "muted", Kind::Text),
+    ("name", "name", Kind::Text),
+    ("novalidate", "novalidate", Kind::Text),
+    ("open", "open", Kind::Text),
+    ("optimum", "optimum", Kind::Text),
+    ("pattern", "pattern", Kind::Text),
+    ("ping", "ping", Kind::Text),
+    ("placeholder", "placeholder", Kind::Text),
+    ("poster", "poster", Kind::Text),
+    ("preload", "preload", Kind::Text),
+    ("radiogroup", "radiogroup", Kind::Text),
+    ("readonly", "readonly", Kind::Bool),
+    ("referrerpolicy", "referrerpolicy", Kind::Text),
+    ("rel", "rel", Kind::Text),
+    ("required", "required", Kind::Bool),
+    ("reversed", "reversed", Kind::Text),
+    ("rows", "rows", Kind::Text),
+    ("rowspan", "rowspan", Kind::Text),
+    ("sandbox", "sandbox", Kind::Text),
+    ("scope", "scope", Kind::Text),
+    ("scoped", "scoped", Kind::Text),
+    ("selected", "selected", Kind::Bool),
+    ("shape", "shape", Kind::Text),
+    ("size", "size", Kind::Text),
+    ("sizes", "sizes", Kind::Text),
+    ("slot", "slot", Kind::Text),
+    ("span", "span", Kind::Text),
+    ("spellcheck", "spellcheck", Kind::Text),
+    ("src", "src", Kind::Text),
+    ("srcdoc", "srcdoc", Kind::Text),
+    ("srclang", "srclang", Kind::Text),
+    ("srcset", "srcset", Kind::Text),
+    ("start", "start", Kind::Text),
+    ("step", "step", Kind::Text),
+    ("style", "style", Kind::Text),
+    ("summary", "summary", Kind::Text),
+    ("tabindex", "tabindex", Kind::Text),
+    ("target", "target", Kind::Text),
+    ("title", "title", Kind::Text),
+    ("translate", "translate", Kind::Text),
+    ("type", "type_", Kind::Text),
+    ("usemap", "usemap", Kind::Text),
+    ("value", "value", Kind::Text),
+    ("width", "width", Kind::Text),
+    ("wrap", "wrap", Kind::Text),
+];
+
+/// Attributes whose constructors set the DOM property instead, since the
+/// attribute only holds the initial value. They are written by hand.
+pub const PROPERTIES: &[&str] = &["checked", "selected", "value"];
+
+/// Attributes every element has.
+// https://html.spec.whatwg.org/multipage/dom.html#global-attributes
+pub const GLOBAL_ATTRIBUTES: &[&str] = &[
+    "accesskey",
+    "autocapitalize",
+    "autofocus",
+    "class",
+    "contenteditable",
+    "contextmenu",
+    "dir",
+    "draggable",
+    "enterkeyhint",
+    "hidden",
+    "id",
+    "inputmode",
+    "itemprop",
+    "lang",
+    "slot",
+    "spellcheck",
+    "style",
+    "tabindex",
+    "title",
+    "translate",
+];
+
+/// The attributes each element has besides the global ones, from
+/// https://developer.mozilla.org/en-US/docs/Web/HTML/Attributes. Elements that
+/// aren't listed only have the global attributes.
+pub const ELEMENT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    (
+        "a",
+        &[
+            "download",
+            "href",
+            "hreflang",
+            "media",
+            "ping",
+            "referrerpolicy",
+            "rel",
+            "shape",
+            "target",
+        ],
+    ),
+    (
+        "area",
+        &[
+            "alt",
+            "coords",
+            "download",
+            "href",
+            "hreflang",
+            "media",
+            "ping",
+            "referrerpolicy",
+            "rel",
+            "shape",
+            "target",
+        ],
+    ),
+    (
+        "audio",
+        &[
+            "autoplay",
+            "buffered",
+            "controls",
+            "crossorigin",
+            "loop",
+            "muted",
+            "preload",
+            "src",
+        ],
+    ),
+    ("blockquote", &["cite"]),
+    (
+        "button",
+        &[
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "name",
+            "type",
+            "value",
+        ],
+    ),
+    ("canvas", &["height", "width"]),
+    ("caption", &["align"]),
+    ("col", &["align", "span"]),
+    ("colgroup", &["align", "span"]),
+    ("data", &["value"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open"]),
+    ("dialog", &["open"]),
+    ("embed", &["height", "src", "type", "width"]),
+    ("fieldset", &["disabled", "form", "name"]),
+    (
+        "form",
+        &[
+            "accept",
+            "accept-charset",
+            "action",
+            "autocomplete",
+            "enctype",
+            "method",
+            "name",
+            "novalidate",
+            "target",
+        ],
+    ),
+    ("hr", &["align", "color"]),
+    (
+        "iframe",
+        &[
+            "align",
+            "allow",
+            "csp",
+            "height",
+            "importance",
+            "loading",
+            "name",
+            "referrerpolicy",
+            "sandbox",
+            "src",
+            "srcdoc",
+            "width",
+        ],
+    ),
+    (
+        "img",
+        &[
+            "align",
+            "alt",
+            "crossorigin",
+            "decoding",
+            "height",
+            "importance",
+            "intrinsicsize",
+            "ismap",
+            "loading",
+            "referrerpolicy",
+            "sizes",
+            "src",
+            "srcset",
+            "usemap",
+            "width",
+        ],
+    ),
+    (
+        "input",
+        &[
+            "accept",
+            "alt",
+            "autocomplete",
+            "capture",
+            "checked",
+            "dirname",
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "height",
+            "list",
+            "max",
+            "maxlength",
+            "min",
+            "minlength",
+            "multiple",
+            "name",
+            "pattern",
+            "placeholder",
+            "readonly",
+            "required",
+            "size",
+            "src",
+            "step",
+            "type",
+            "usemap",
+            "value",
+            "width",
+        ],
+    ),
+    ("ins", &["cite", "datetime"]),
+    ("label", &["for", "form"]),
+    ("li", &["value"]),
+    ("menu", &["type"]),
+    (
+        "meter",
+        &["form", "high", "low", "max", "min", "optimum", "value"],
+    ),
+    (
+        "object",
+        &["data", "form", "height", "name", "type", "usemap", "width"],
+    ),
+    ("ol", &["reversed", "start", "type"]),
+    ("optgroup", &["disabled", "label"]),
+    ("option", &["disabled", "label", "selected", "value"]),
+    ("output", &["for", "form", "name"]),
+    ("param", &["name", "value"]),
+    ("progress", &["max", "value"]),
+    ("q", &["cite"]),
+    (
+        "script",
+        &[
+            "async",
+            "charset",
+            "crossorigin",
+            "defer",
+            "importance",
+            "integrity",
+            "language",
+            "referrerpolicy",
+            "src",
+            "type",
+        ],
+    ),
+    (
+        "select",
+        &[
+            "autocomplete",
+            "disabled",
+            "form",
+            "multiple",
+            "name",
+            "required",
+            "size",
+            "value",
+        ],
+    ),
+    ("slot", &["name"]),
+    ("source", &["media", "sizes", "src", "srcset", "type"]),
+    ("table", &["align", "summary"]),
+    ("tbody", &["align"]),
+    ("td", &["align", "colspan", "headers", "rowspan"]),
+    (
+        "textarea",
+        &[
+            "autocomplete",
+            "cols",
+            "dirname",
+            "disabled",
+            "form",
+            "maxlength",
+            "minlength",
+            "name",
+            "placeholder",
+            "readonly",
+            "required",
+            "rows",
+            "value",
+            "wrap",
+        ],
+    ),
+    ("tfoot", &["align"]),
+    ("th", &["align", "colspan", "headers", "rowspan", "scope"]),
+    ("thead", &["align"]),
+    ("time", &["datetime"]),
+    ("tr", &["align"]),
+    ("track", &["default", "kind", "label", "src", "srclang"]),
+    (
+        "video",
+        &[
+            "autoplay",
+            "buffered",
+            "controls",
+            "crossorigin",
+            "height",
+            "loop",
+            "muted",
+            "poster",
+            "preload",
+            "src",
+            "width",
+        ],
+    ),
+];
+
+pub fn is_element(name: &str) -> bool {
+    ELEMENTS.contains(&name)
+}
+
+pub fn attribute(name: &str) -> Option<(&'static str, Kind)> {
+    ATTRIBUTES
+        .iter()
+        .find(|(other, _, _)| *other == name)
+        .map(|(_, function, kind)| (*function, *kind))
+}
@@ -0,0 +1,577 @@
+//! The markup of `include_html!`: an HTML file, read and parsed while
+//! compiling into the nodes `html!` expands.
+
+use crate::{
+    html::{Attribute, Element, Html, Name, Node, Value},
+    spec::{self, Kind},
+};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::{env, fs, path::PathBuf};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, Error, Expr, Ident, LitStr, Result, Token,
+};
+
+// https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+// https://html.spec.whatwg.org/multipage/syntax.html#raw-text-elements
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// The arguments of `include_html!`: the path of the file, and the bindings
+/// its event attributes name.
+pub struct Template {
+    path: LitStr,
+    bindings: Vec<Binding>,
+}
+
+struct Binding {
+    name: Ident,
+    expr: Expr,
+}
+
+impl Parse for Template {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path = input.parse()?;
+        let mut bindings: Vec<Binding> = vec![];
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let expr = input.parse()?;
+
+            if bindings.iter().any(|binding| binding.name == name) {
+                return Err(Error::new(
+                    name.span(),
+                    format!("`{}` is bound more than once", name),
+                ));
+            }
+
+            bindings.push(Binding { name, expr });
+        }
+
+        Ok(Template { path, bindings })
+    }
+}
+
+impl Template {
+    pub fn expand(&self) -> Result<TokenStream> {
+        let directory = env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| Error::new(self.path.span(), "CARGO_MANIFEST_DIR isn't set"))?;
+        let path = PathBuf::from(directory).join(self.path.value());
+        let source = fs::read_to_string(&path).map_err(|error| {
+            Error::new(
+                self.path.span(),
+                format!("couldn't read {}: {}", path.display(), error),
+            )
+        })?;
+
+        let mut parser = Parser {
+            source: source.chars().collect(),
+            position: 0,
+            file: self.path.value(),
+            span: self.path.span(),
+            bindings: &self.bindings,
+            used: vec![false; self.bindings.len()],
+        };
+        let mut nodes = parser.children(None)?;
+
+        if nodes.len() != 1 {
+            return Err(parser.error(0, "must have a single root node"));
+        }
+
+        if let Some(index) = parser.used.iter().position(|used| !used) {
+            let name = &self.bindings[index].name;
+
+            return Err(Error::new(
+                name.span(),
+                format!("`{}` isn't used by {}", name, parser.file),
+            ));
+        }
+
+        let html = Html(nodes.remove(0)).expand()?;
+        let path = path.to_string_lossy();
+
+        Ok(quote! {
+            {
+                // rebuilds when the file changes
+                const _: &str = ::core::include_str!(#path);
+                #html
+            }
+        })
+    }
+}
+
+struct Parser<'a> {
+    source: Vec<char>,
+    position: usize,
+    file: String,
+    // where everything in the file is attributed to
+    span: Span,
+    bindings: &'a [Binding],
+    used: Vec<bool>,
+}
+
+// A piece of text or of an attribute value.
+enum Segment {
+    Text(String),
+    Expr(Box<Expr>),
+}
+
+impl<'a> Parser<'a> {
+    // Parses nodes until the end tag of `parent`, given by its name and where
+    // it starts, or until the end of the file.
+    fn children(&mut self, parent: Option<(&str, usize)>) -> Result<Vec<Node>> {
+        let mut nodes = vec![];
+
+        loop {
+            if self.position == self.source.len() {
+                return match parent {
+                    Some((name, start)) => {
+                        Err(self.error(start, &format!("`<{}>` is never closed", name)))
+                    }
+                    None => Ok(nodes),
+                };
+            }
+
+            if self.starts_with("<!--") {
+                let start = self.position;
+
+                match self.find("-->", start) {
+                    Some(end) => self.position = end + 3,
+                    None => return Err(self.error(start, "comment is never closed")),
+                }
+            } else if self.starts_with("</") {
+                let start = self.position;
+                self.position += 2;
+                let name = self.name();
+                self.skip_whitespace();
+                self.expect('>', start)?;
+
+                return match parent {
+                    Some((parent, _)) if parent == name => Ok(nodes),
+                    Some((parent, _)) => {
+                        Err(self.error(start, &format!("expected `</{}>`", parent)))
+                    }
+                    None => Err(self.error(start, &format!("unexpected `</{}>`", name))),
+                };
+            } else if self.starts_with("<") {
+                nodes.push(Node::Element(self.element()?));
+            } else {
+                self.text(&mut nodes)?;
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element> {
+        let start = self.position;
+        self.position += 1;
+
+        let name = self.name();
+        if name.is_empty() {
+            return Err(self.error(start, "expected a tag name after `<`"));
+        }
+
+        let mut attributes = vec![];
+        let self_closing = loop {
+            self.skip_whitespace();
+
+            if self.starts_with("/>") {
+                self.position += 2;
+                break true;
+            } else if self.starts_with(">") {
+                self.position += 1;
+                break false;
+            } else if self.position == self.source.len() {
+                return Err(self.error(start, &format!("`<{}` is never closed", name)));
+            }
+
+            attributes.push(self.attribute()?);
+        };
+
+        let mut element = Element {
+            name: self.name_at(&name),
+            attributes,
+            children: vec![],
+        };
+
+        // checks the tag and attributes here, where the error can point to
+        element
+            .expand()
+            .map_err(|error| self.error(start, &error.to_string()))?;
+
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            return Ok(element);
+        }
+
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let end_tag = format!("</{}", name);
+            let end = self
+                .find(&end_tag, self.position)
+                .ok_or_else(|| self.error(start, &format!("`<{}>` is never closed", name)))?;
+            let text: String = self.source[self.position..end].iter().collect();
+
+            if !text.is_empty() {
+                element
+                    .children
+                    .push(Node::Text(LitStr::new(&text, self.span)));
+            }
+
+            self.position = end + end_tag.chars().count();
+            self.skip_whitespace();
+            self.expect('>', end)?;
+
+            return Ok(element);
+        }
+
+        element.children = self.children(Some((&name, start)))?;
+
+        Ok(element)
+    }
+
+    fn attribute(&mut self) -> Result<Attribute> {
+        let start = self.position;
+        let name = self
+            .take_while(|character| !character.is_whitespace() && !"/>=\"'<".contains(character));
+
+        if name.is_empty() {
+            return Err(self.error(
+                start,
+                &format!("unexpected `{}`", self.source[self.position]),
+            ));
+        }
+
+        let name = name.to_ascii_lowercase();
+
+        self.skip_whitespace();
+        if !self.starts_with("=") {
+            return Ok(Attribute {
+                name: self.name_at(&name),
+                value: Value::None,
+            });
+        }
+        self.position += 1;
+        self.skip_whitespace();
+
+        let (raw, value_start) = match self.source.get(self.position) {
+            Some(quote @ '"') | Some(quote @ '\'') => {
+                let quote = *quote;
+                let value_start = self.position + 1;
+                let end = self
+                    .find(&quote.to_string(), value_start)
+                    .ok_or_else(|| self.error(start, "attribute value is never closed"))?;
+                self.position = end + 1;
+
+                (self.source[value_start..end].iter().collect(), value_start)
+            }
+            _ => {
+                let value_start = self.position;
+                let raw =
+                    self.take_while(|character| !character.is_whitespace() && character != '>');
+
+                if raw.is_empty() {
+                    return Err(self.error(start, &format!("`{}` has no value after `=`", name)));
+                }
+
+                (raw, value_start)
+            }
+        };
+
+        let value = if matches!(name.as_str(), "onclick" | "oninput" | "onsubmit")
+            || name.starts_with("on:")
+        {
+            Value::Expr(Box::new(self.binding(raw.trim(), value_start)?))
+        } else if name.starts_with("on") && spec::attribute(&name).is_none() {
+            return Err(self.error(
+                start,
+                &format!(
+                    "unknown event attribute `{}`, use `on:{}=\"binding\"` for other events",
+                    name,
+                    &name[2..]
+                ),
+            ));
+        } else {
+            let segments = self.segments(&raw, value_start)?;
+            let kind = spec::attribute(&name).map(|(_, kind)| kind);
+
+            match segments.as_slice() {
+                // present, whatever the value
+                _ if kind == Some(Kind::Bool)
+                    && segments
+                        .iter()
+                        .all(|segment| matches!(segment, Segment::Text(_))) =>
+                {
+                    Value::None
+                }
+                [] => Value::Lit(LitStr::new("", self.span)),
+                [Segment::Text(text)] => Value::Lit(LitStr::new(text, self.span)),
+                [Segment::Expr(expr)] if kind == Some(Kind::Bool) => Value::Expr(expr.clone()),
+                _ => Value::Expr(Box::new(self.format(&segments))),
+            }
+        };
+
+        Ok(Attribute {
+            name: self.name_at(&name),
+            value,
+        })
+    }
+
+    // Text up to the next tag, with its holes, as nodes. Formatting between
+    // tags is left out.
+    fn text(&mut self, nodes: &mut Vec<Node>) -> Result<()> {
+        let start = self.position;
+        let raw = self.take_while(|character| character != '<');
+
+        if raw.trim().is_empty() {
+            return Ok(());
+        }
+
+        for segment in self.segments(&raw, start)? {
+            nodes.push(match segment {
+                Segment::Text(text) => Node::Text(LitStr::new(&text, self.span)),
+                Segment::Expr(expr) => Node::Expr(*expr),
+            });
+        }
+
+        Ok(())
+    }
+
+    // Splits `raw`, which starts at `start` in the file, into text, with its
+    // character references decoded, and `{expression}` holes. `{{` and `}}`
+    // are literal braces.
+    fn segments(&self, raw: &str, start: usize) -> Result<Vec<Segment>> {
+        let characters: Vec<char> = raw.chars().collect();
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut index = 0;
+
+        while index < characters.len() {
+            let next = characters.get(index + 1).copied();
+
+            match characters[index] {
+                '{' if next == Some('{') => {
+                    text.push('{');
+                    index += 2;
+                }
+                '}' if next == Some('}') => {
+                    text.push('}');
+                    index += 2;
+                }
+                '{' => {
+                    let mut depth = 0;
+                    let end = (index..characters.len())
+                        .find(|&end| {
+                            match characters[end] {
+                                '{' => depth += 1,
+                                '}' => depth -= 1,
+                                _ => {}
+                            }
+                            depth == 0
+                        })
+                        .ok_or_else(|| self.error(start + index, "`{` is never closed"))?;
+
+                    let source: String = characters[index + 1..end].iter().collect();
+                    let expr = syn::parse_str::<Expr>(&source).map_err(|error| {
+                        self.error(start + index, &format!("invalid expression: {}", error))
+                    })?;
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Expr(Box::new(expr)));
+                    index = end + 1;
+                }
+                '}' => {
+                    return Err(
+                        self.error(start + index, "unmatched `}`, use `}}` for a literal one")
+                    )
+                }
+                '&' => {
+                    // https://html.spec.whatwg.org/multipage/syntax.html#character-references
+                    let reference: String = characters[index + 1..]
+                        .iter()
+                        .take_while(|character| {
+                            character.is_ascii_alphanumeric() || **character == '#'
+                        })
+                        .collect();
+
+                    if characters.get(index + 1 + reference.len()) != Some(&';') {
+                        // a lone `&`
+                        text.push('&');
+                        index += 1;
+                        continue;
+                    }
+
+                    let character = decode(&reference).ok_or_else(|| {
+                        self.error(
+                            start + index,
+                            &format!("unknown character reference `&{};`", reference),
+                        )
+                    })?;
+
+                    text.push(character);
+                    index += reference.len() + 2;
+                }
+                character => {
+                    text.push(character);
+                    index += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(segments)
+    }
+
+    // An attribute value with text and holes, as a `format!`.
+    fn format(&self, segments: &[Segment]) -> Expr {
+        let mut format = String::new();
+        let mut exprs = vec![];
+
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => format.push_str(&text.replace('{', "{{").replace('}', "}}")),
+                Segment::Expr(expr) => {
+                    format.push_str("{}");
+                    exprs.push(expr);
+                }
+            }
+        }
+
+        let format = LitStr::new(&format, self.span);
+
+        parse_quote! { ::std::format!(#format, #(#exprs),*) }
+    }
+
+    fn binding(&mut self, name: &str, start: usize) -> Result<Expr> {
+        match self
+            .bindings
+            .iter()
+            .position(|binding| binding.name == name)
+        {
+            Some(index) => {
+                self.used[index] = true;
+                Ok(self.bindings[index].expr.clone())
+            }
+            None => Err(self.error(
+                start,
+                &format!(
+                    "no binding named `{}`, pass it as `{} = expression`",
+                    name, name
+                ),
+            )),
+        }
+    }
+
+    fn name(&mut self) -> String {
+        self.take_while(|character| character.is_ascii_alphanumeric() || character == '-')
+            .to_ascii_lowercase()
+    }
+
+    fn name_at(&self, value: &str) -> Name {
+        Name {
+            value: value.into(),
+            span: self.span,
+        }
+    }
+
+    fn take_while<F>(&mut self, predicate: F) -> String
+    where
+        F: Fn(char) -> bool,
+    {
+        let start = self.position;
+
+        while self.position < self.source.len() && predicate(self.source[self.position]) {
+            self.position += 1;
+        }
+
+        self.source[start..self.position].iter().collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        self.find(text, self.position) == Some(self.position)
+    }
+
+    // The position of the next `text` from `start`.
+    fn find(&self, text: &str, start: usize) -> Option<usize> {
+        let text: Vec<char> = text.chars().collect();
+
+        (start..=self.source.len().saturating_sub(text.len()))
+            .find(|&position| self.source[position..].starts_with(&text))
+    }
+
+    fn expect(&mut self, character: char, start: usize) -> Result<()> {
+        if self.source.get(self.position) == Some(&character) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(start, &format!("expected `{}`", character)))
+        }
+    }
+
+    // An error about the markup at `position`, reported as `file:line:column`
+    // since the file itself can't be pointed to.
+    fn error(&self, position: usize, message: &str) -> Error {
+        let before = &self.source[..position.min(self.source.len())];
+        let line = before
+            .iter()
+            .filter(|character| **character == '\n')
+            .count()
+            + 1;
+        let column = before
+            .iter()
+            .rev()
+            .take_while(|character| **character != '\n')
+            .count()
+            + 1;
+
+        Error::new(
+            self.span,
+            format!("{}:{}:{}: {}", self.file, line, column, message),
+        )
+    }
+}
+
+fn decode(reference: &str) -> Option<char> {
+    if let Some(number) = reference.strip_prefix('#') {
+        let code = match number
+            .strip_prefix('x')
+            .or_else(|| number.strip_prefix('X'))
+        {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+
+        return std::char::from_u32(code);
+    }
+
+    Some(match reference {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        _ => return None,
+    })
+}
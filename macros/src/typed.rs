@@ -0,0 +1,139 @@
+//! The typed element and attribute constructors, generated from the tables in
+//! `spec`. Each element gets a type, and each attribute a trait in `has` that
+//! the types of the elements with that attribute implement.
+
+use crate::spec::{self, Kind};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+/// The element types and their constructors, and the `has` traits.
+pub fn elements() -> TokenStream {
+    let types = spec::ELEMENTS.iter().map(|name| {
+        let element = type_name(name);
+        let doc = format!("`<{}>`", name);
+
+        quote! {
+            #[doc = #doc]
+            pub enum #element {}
+
+            impl super::has::Global for #element {}
+        }
+    });
+
+    let functions = spec::ELEMENTS.iter().map(|name| {
+        let element = type_name(name);
+        let function = ident(name);
+
+        quote! {
+            pub fn #function<Message>(
+                attributes: Vec<TypedAttribute<element::#element, Message>>,
+                children: Vec<Html<Message>>,
+            ) -> Html<Message> {
+                Html::#function(attributes.into_iter().map(Attribute::from).collect(), children)
+            }
+        }
+    });
+
+    let has_items = typed_attributes().map(|(name, elements)| {
+        let trait_name = type_name(name);
+        let doc = format!("Elements with the `{}` attribute.", name);
+        let elements = elements.iter().map(|element| type_name(element));
+
+        quote! {
+            #[doc = #doc]
+            pub trait #trait_name: Global {}
+
+            #(impl #trait_name for super::element::#elements {})*
+        }
+    });
+
+    quote! {
+        /// The types of elements, which only tag `TypedAttribute`s.
+        pub mod element {
+            #(#types)*
+        }
+
+        #(#functions)*
+
+        /// The attributes of each element type.
+        pub mod has {
+            /// Elements, which all have the global attributes and event
+            /// handlers.
+            pub trait Global {}
+
+            #(#has_items)*
+        }
+    }
+}
+
+/// The constructors of the attributes in the tables, for the elements that
+/// have them.
+pub fn attributes() -> TokenStream {
+    let global = spec::GLOBAL_ATTRIBUTES
+        .iter()
+        .map(|name| attribute(name, quote! { has::Global }));
+    let specific = typed_attributes().map(|(name, _)| {
+        let trait_name = type_name(name);
+        attribute(name, quote! { has::#trait_name })
+    });
+
+    quote! {
+        #(#global)*
+        #(#specific)*
+    }
+}
+
+fn attribute(name: &str, bound: TokenStream) -> TokenStream {
+    let (function, kind) = spec::attribute(name).unwrap();
+    let function = ident(function);
+    let value = match kind {
+        Kind::Text => quote! { &str },
+        Kind::Bool => quote! { bool },
+    };
+
+    quote! {
+        pub fn #function<Element, Message>(value: #value) -> TypedAttribute<Element, Message>
+        where
+            Element: #bound,
+        {
+            TypedAttribute::unchecked(Attribute::#function(value))
+        }
+    }
+}
+
+// The attributes that aren't global, with the elements that have them.
+fn typed_attributes() -> impl Iterator<Item = (&'static str, Vec<&'static str>)> {
+    spec::ATTRIBUTES
+        .iter()
+        .map(|(name, _, _)| {
+            let elements = spec::ELEMENT_ATTRIBUTES
+                .iter()
+                .filter(|(_, attributes)| attributes.contains(name))
+                .map(|(element, _)| *element)
+                .collect::<Vec<_>>();
+
+            (*name, elements)
+        })
+        .filter(|(name, elements)| !elements.is_empty() && !spec::GLOBAL_ATTRIBUTES.contains(name))
+}
+
+fn ident(name: &str) -> Ident {
+    Ident::new(name, Span::call_site())
+}
+
+// `accept-charset` to `AcceptCharset`
+fn type_name(name: &str) -> Ident {
+    let name = name
+        .split('-')
+        .map(|part| {
+            let mut characters = part.chars();
+            match characters.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + characters.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+
+    ident(&name)
+}
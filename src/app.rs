@@ -1,7 +1,22 @@
-use crate::{command::Commands, renderer::Renderer, virtual_dom::Html};
-use log::info;
-use std::{cell::Ref, cell::RefCell, fmt, rc::Rc};
-use wasm_bindgen::prelude::*;
+use crate::{
+    command::Commands,
+    debugger::{Debugger, Session},
+    delegation::Delegation,
+    json,
+    renderer::Renderer,
+    root::Root,
+    subscription::{self, Listener, Sink, Subs},
+    virtual_dom::Html,
+};
+use log::{error, info};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::{HashMap, VecDeque},
+    fmt,
+    rc::Rc,
+};
+use wasm_bindgen::{prelude::*, JsCast};
 use wasm_bindgen_futures::spawn_local;
 
 struct State<Model, Message> {
@@ -18,17 +33,48 @@ where
     }
 }
 
-type InitFn<Model, Message> = dyn Fn() -> (Model, Commands<Message>);
+type InitFn<Model, Message> = dyn Fn(&JsValue) -> Result<(Model, Commands<Message>), JsValue>;
 type UpdateFn<Model, Message> = dyn Fn(Message, &mut Model) -> Commands<Message>;
 type ViewFn<Model, Message> = dyn Fn(&Model) -> Html<Message>;
+type SubscriptionsFn<Model, Message> = dyn Fn(&Model) -> Subs<Message>;
+type MessageObserverFn<Message> = dyn Fn(&Message);
+
+struct ActiveSubscription<Message> {
+    // shared with the listener's sink, so that events are turned into messages
+    // by the latest subscription with this key
+    subscription: Rc<RefCell<subscription::Boxed<Message>>>,
+    // torn down when dropped
+    _listener: Listener,
+}
+
+// keyed by subscription id and the number of earlier subscriptions with the same id
+type ActiveSubscriptions<Message> = HashMap<(String, usize), ActiveSubscription<Message>>;
 
 #[derive(Clone)]
 pub struct App<Model, Message> {
     init: Rc<InitFn<Model, Message>>,
     update: Rc<UpdateFn<Model, Message>>,
     view: Rc<ViewFn<Model, Message>>,
-    root_id: String,
+    subscriptions: Rc<SubscriptionsFn<Model, Message>>,
+    on_message: Vec<Rc<MessageObserverFn<Message>>>,
+    root: Root,
     state: Rc<RefCell<Option<State<Model, Message>>>>,
+    active_subscriptions: Rc<RefCell<ActiveSubscriptions<Message>>>,
+    render_synchronously: bool,
+    hydrate: bool,
+    queue: Rc<RefCell<VecDeque<Message>>>,
+    // set while messages are being applied, so that messages dispatched in the
+    // meantime are queued instead of re-entering update and view
+    processing: Rc<Cell<bool>>,
+    // id of the requested animation frame, if any
+    frame: Rc<RefCell<Option<i32>>>,
+    debug: bool,
+    debugger: Rc<RefCell<Option<Debugger<Model, Message>>>>,
+    // incremented when the app is stopped, so that commands still in flight
+    // from before can be ignored
+    generation: Rc<Cell<usize>>,
+    // the event handlers of the rendered view, created on the first render
+    delegation: Rc<RefCell<Option<Delegation<Message>>>>,
 }
 
 impl<Model, Message> App<Model, Message>
@@ -36,34 +82,329 @@ where
     Model: 'static + Clone + fmt::Debug + Eq,
     Message: 'static + Clone + fmt::Debug,
 {
-    pub fn new<Init, Update, View>(init: Init, update: Update, view: View, root_id: &str) -> Self
+    /// Creates an app rendering into `root`, which is either the id of an
+    /// element in the document, an `Element` or a `ShadowRoot`.
+    pub fn new<Init, Update, View, R>(init: Init, update: Update, view: View, root: R) -> Self
     where
+        R: Into<Root>,
         Init: 'static + Fn() -> (Model, Commands<Message>),
         Update: 'static + Fn(Message, &mut Model) -> Commands<Message>,
         View: 'static + Fn(&Model) -> Html<Message>,
+    {
+        Self::from_init(Rc::new(move |_| Ok(init())), update, view, root.into())
+    }
+
+    /// Creates an app whose `init` receives flags passed in from JavaScript
+    /// with `start_with_flags`, deserialized into `Flags`.
+    pub fn with_flags<Flags, Init, Update, View, R>(
+        init: Init,
+        update: Update,
+        view: View,
+        root: R,
+    ) -> Self
+    where
+        R: Into<Root>,
+        Flags: DeserializeOwned,
+        Init: 'static + Fn(Flags) -> (Model, Commands<Message>),
+        Update: 'static + Fn(Message, &mut Model) -> Commands<Message>,
+        View: 'static + Fn(&Model) -> Html<Message>,
+    {
+        let init = move |flags: &JsValue| {
+            let flags = json::from_js_value(flags).map_err(|e| {
+                JsValue::from(format!(
+                    "failed to decode flags {:?}: {}",
+                    flags,
+                    e.as_string().unwrap_or_default()
+                ))
+            })?;
+
+            Ok(init(flags))
+        };
+
+        Self::from_init(Rc::new(init), update, view, root.into())
+    }
+
+    fn from_init<Update, View>(
+        init: Rc<InitFn<Model, Message>>,
+        update: Update,
+        view: View,
+        root: Root,
+    ) -> Self
+    where
+        Update: 'static + Fn(Message, &mut Model) -> Commands<Message>,
+        View: 'static + Fn(&Model) -> Html<Message>,
     {
         Self {
-            init: Rc::new(init),
+            init,
             update: Rc::new(update),
             view: Rc::new(view),
-            root_id: root_id.into(),
+            subscriptions: Rc::new(|_| vec![]),
+            on_message: vec![],
+            root,
             state: Rc::new(RefCell::new(None)),
+            active_subscriptions: Rc::new(RefCell::new(HashMap::new())),
+            render_synchronously: false,
+            hydrate: false,
+            queue: Rc::new(RefCell::new(VecDeque::new())),
+            processing: Rc::new(Cell::new(false)),
+            frame: Rc::new(RefCell::new(None)),
+            debug: false,
+            debugger: Rc::new(RefCell::new(None)),
+            generation: Rc::new(Cell::new(0)),
+            delegation: Rc::new(RefCell::new(None)),
         }
     }
 
+    /// Sets the function deciding which subscriptions are active for a given
+    /// model. It is called again after every update; listeners are started
+    /// for new subscriptions and torn down for ones no longer returned.
+    pub fn subscriptions<Subscriptions>(mut self, subscriptions: Subscriptions) -> Self
+    where
+        Subscriptions: 'static + Fn(&Model) -> Subs<Message>,
+    {
+        self.subscriptions = Rc::new(subscriptions);
+        self
+    }
+
+    /// Calls `observer` with every message just before it is applied, after
+    /// the observers added before it.
+    pub fn on_message<Observer>(mut self, observer: Observer) -> Self
+    where
+        Observer: 'static + Fn(&Message),
+    {
+        self.on_message.push(Rc::new(observer));
+        self
+    }
+
+    /// Updates and renders as soon as each message is handled, instead of
+    /// batching messages until the next animation frame. Useful in tests.
+    pub fn render_synchronously(mut self) -> Self {
+        self.render_synchronously = true;
+        self
+    }
+
+    /// Adopts the markup already in the root, such as a server-rendered
+    /// view, on the first render instead of rendering from scratch. Nodes
+    /// that don't match the first view are logged and repaired.
+    pub fn hydrate(mut self) -> Self {
+        self.hydrate = true;
+        self
+    }
+
+    /// Records every message and the model it produced, so that earlier
+    /// models can be shown again with `jump_to`.
+    pub fn debug(mut self) -> Self {
+        self.debug = true;
+        self
+    }
+
     pub fn start(&self) {
-        let (model, commands) = (self.init)();
+        self.start_with_flags(JsValue::UNDEFINED).unwrap();
+    }
+
+    /// Starts the app, passing `flags` to `init`. Fails without rendering
+    /// anything if the root can't be found or the flags can't be decoded.
+    pub fn start_with_flags(&self, flags: JsValue) -> Result<(), JsValue> {
+        self.root.node()?;
+
+        let (model, commands) = (self.init)(&flags)?;
+
+        self.processing.set(true);
+
+        if self.debug {
+            self.debugger.replace(Some(Debugger::new(model.clone())));
+        }
 
         self.update(model, commands);
+
+        self.processing.set(false);
+
+        if self.render_synchronously {
+            self.process_queue();
+        } else if !self.queue.borrow().is_empty() {
+            self.request_frame();
+        }
+
+        Ok(())
     }
 
+    /// Unmounts the app: removes everything from the root, tears down its
+    /// subscriptions and event handlers, and ignores commands that are still
+    /// in flight. The app can be started again afterwards.
+    pub fn stop(&self) -> Result<(), JsValue> {
+        if self.processing.get() {
+            return Err("can't stop while a message is being handled".into());
+        }
+
+        self.generation.set(self.generation.get() + 1);
+
+        self.drop_queued_messages()?;
+        self.active_subscriptions.take();
+        self.state.take();
+        self.debugger.take();
+        self.delegation.take();
+
+        self.root.node()?.set_text_content(None);
+
+        Ok(())
+    }
+
+    /// Queues a message. Messages are always applied one at a time in the
+    /// order they were queued, whether they come from the UI, commands or
+    /// subscriptions, and a message queued while another is being applied
+    /// waits for it to finish.
     pub fn handle_message(&self, message: Message) {
         info!("message: {:#?}", message);
 
+        if !self.processing.get() && self.state().is_none() {
+            info!("app isn't running, ignoring message");
+            return;
+        }
+
+        if self.is_paused() {
+            info!("debugger is paused, ignoring message");
+            return;
+        }
+
+        self.queue.borrow_mut().push_back(message);
+
+        if self.render_synchronously {
+            self.process_queue();
+        } else {
+            self.request_frame();
+        }
+    }
+
+    /// The debugger's history, if the app was started with `debug`.
+    pub fn debugger(&self) -> Option<Ref<'_, Debugger<Model, Message>>> {
+        Ref::filter_map(self.debugger.borrow(), Option::as_ref).ok()
+    }
+
+    /// Renders the model after the first `index` recorded messages, where 0 is
+    /// the initial model, and ignores new messages until `resume` is called.
+    /// Jumping to the latest model resumes immediately.
+    pub fn jump_to(&self, index: usize) {
+        if self.processing.get() {
+            error!("can't jump to {} while a message is being handled", index);
+            return;
+        }
+
+        let model = {
+            let mut debugger = self.debugger.borrow_mut();
+            let debugger = match debugger.as_mut() {
+                Some(debugger) => debugger,
+                None => {
+                    error!("can't jump to {} without debug enabled", index);
+                    return;
+                }
+            };
+
+            match debugger.jump_to(index) {
+                Some(model) => model.clone(),
+                None => {
+                    error!("no model recorded at {}", index);
+                    return;
+                }
+            }
+        };
+
+        // messages waiting for the next frame were meant for the latest model
+        if let Err(err) = self.drop_queued_messages() {
+            error!("failed to drop queued messages: {:?}", err);
+        }
+
+        self.processing.set(true);
+        self.update(model, vec![]);
+        self.processing.set(false);
+    }
+
+    /// Continues handling messages from the model being shown, discarding the
+    /// history recorded after it.
+    pub fn resume(&self) {
+        if let Some(debugger) = self.debugger.borrow_mut().as_mut() {
+            debugger.resume();
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.debugger()
+            .is_some_and(|debugger| debugger.paused_at().is_some())
+    }
+
+    fn request_frame(&self) {
+        if self.frame.borrow().is_some() {
+            return;
+        }
+
+        let app = self.clone();
+        let callback = Closure::once_into_js(move || {
+            app.frame.replace(None);
+            app.process_queue();
+        });
+
+        let id = web_sys::window()
+            .unwrap()
+            .request_animation_frame(callback.unchecked_ref())
+            .unwrap();
+
+        self.frame.replace(Some(id));
+    }
+
+    fn drop_queued_messages(&self) -> Result<(), JsValue> {
+        self.queue.borrow_mut().clear();
+
+        if let Some(frame) = self.frame.take() {
+            web_sys::window().unwrap().cancel_animation_frame(frame)?;
+        }
+
+        Ok(())
+    }
+
+    fn process_queue(&self) {
+        if self.processing.replace(true) {
+            return;
+        }
+
+        if self.render_synchronously {
+            while let Some(message) = self.pop_message() {
+                self.handle_messages(vec![message]);
+            }
+        } else {
+            // messages queued while these are applied wait for the next frame,
+            // which handle_message has already requested
+            let messages = self.queue.replace(VecDeque::new());
+            self.handle_messages(messages.into());
+        }
+
+        self.processing.set(false);
+    }
+
+    fn pop_message(&self) -> Option<Message> {
+        self.queue.borrow_mut().pop_front()
+    }
+
+    // Applies every message to the model in order, then renders once.
+    fn handle_messages(&self, messages: Vec<Message>) {
         let mut new_model = self.state().as_ref().unwrap().model.clone();
-        let commands = (self.update)(message, &mut new_model);
+        let mut commands = vec![];
+
+        for message in messages {
+            let recorded_message = self.debug.then(|| message.clone());
+
+            for observer in &self.on_message {
+                observer(&message);
+            }
+            commands.append(&mut (self.update)(message, &mut new_model));
+
+            if let (Some(message), Some(debugger)) =
+                (recorded_message, self.debugger.borrow_mut().as_mut())
+            {
+                debugger.record(message, new_model.clone());
+            }
+        }
 
         if new_model == self.state().as_ref().unwrap().model {
+            self.handle_commands(commands);
             return;
         }
 
@@ -77,21 +418,88 @@ where
         self.render_app(&new_html).unwrap();
 
         self.handle_commands(commands);
+        self.update_subscriptions(&model);
         self.set_state(State::new(model, new_html));
     }
 
     fn handle_commands(&self, commands: Commands<Message>) {
         for command in commands {
             let app = self.clone();
+            let generation = self.generation.get();
 
             spawn_local(async move {
                 let message = command.run().await;
-                app.handle_message(message);
+
+                if app.generation.get() != generation {
+                    info!("app was stopped, ignoring command result");
+                    return;
+                }
+
+                if let Some(message) = message {
+                    app.handle_message(message);
+                }
             });
         }
     }
 
-    fn state(&self) -> Ref<Option<State<Model, Message>>> {
+    fn update_subscriptions(&self, model: &Model) {
+        let subscriptions = (self.subscriptions)(model);
+
+        let mut previous = self.active_subscriptions.take();
+        let mut active = HashMap::new();
+
+        for subscription in subscriptions {
+            let id = subscription.id();
+            let count = (0..)
+                .find(|count| !active.contains_key(&(id.clone(), *count)))
+                .unwrap();
+            let key = (id, count);
+
+            let entry = match previous.remove(&key) {
+                Some(existing) => {
+                    existing.subscription.replace(subscription);
+                    existing
+                }
+                None => {
+                    let subscription = Rc::new(RefCell::new(subscription));
+                    let listener = subscription
+                        .borrow()
+                        .listen(self.sink(subscription.clone()));
+
+                    match listener {
+                        Ok(listener) => ActiveSubscription {
+                            subscription,
+                            _listener: listener,
+                        },
+                        Err(err) => {
+                            error!("failed to listen to subscription {:?}: {:?}", key, err);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            active.insert(key, entry);
+        }
+
+        self.active_subscriptions.replace(active);
+
+        // anything left in `previous` is torn down when it is dropped here
+    }
+
+    fn sink(&self, subscription: Rc<RefCell<subscription::Boxed<Message>>>) -> Sink {
+        let app = self.clone();
+
+        Sink::new(move |value| {
+            let message = subscription.borrow().message(value);
+
+            if let Some(message) = message {
+                app.handle_message(message);
+            }
+        })
+    }
+
+    fn state(&self) -> Ref<'_, Option<State<Model, Message>>> {
         (self.state).borrow()
     }
 
@@ -100,11 +508,81 @@ where
     }
 
     fn render_app(&self, html: &Html<Message>) -> Result<(), JsValue> {
-        let renderer = Renderer::new(self);
-        renderer.render(
-            self.state().as_ref().map(|state| &state.html),
-            html,
-            &self.root_id,
-        )
+        let root = self.root.node()?;
+
+        if self.delegation.borrow().is_none() {
+            let app = self.clone();
+            let delegation = Delegation::new(root.clone(), move |message| {
+                app.handle_message(message);
+            });
+            self.delegation.replace(Some(delegation));
+        }
+
+        let delegation = self.delegation.borrow();
+        let renderer = Renderer::new(delegation.as_ref().unwrap());
+
+        match self.state().as_ref() {
+            Some(state) => renderer.render(Some(&state.html), html, &root),
+            None if self.hydrate => renderer.hydrate(html, &root),
+            None => renderer.render(None, html, &root),
+        }
+    }
+}
+
+impl<Model, Message> App<Model, Message>
+where
+    Model: 'static + Clone + fmt::Debug + Eq + Serialize + DeserializeOwned,
+    Message: 'static + Clone + fmt::Debug + Serialize + DeserializeOwned,
+{
+    /// Serializes the initial model and every message recorded by the
+    /// debugger as JSON.
+    pub fn export_session(&self) -> Result<String, JsValue> {
+        let session = self
+            .debugger()
+            .ok_or_else(|| JsValue::from("can't export a session without debug enabled"))?
+            .session();
+
+        serde_json::to_string(&session).map_err(|e| e.to_string().into())
+    }
+
+    /// Starts from the initial model of an exported session and applies its
+    /// messages in order, then renders the resulting model. Commands returned
+    /// by `update` are not run, since their results were recorded as messages.
+    pub fn replay(&self, json: &str) -> Result<(), JsValue> {
+        let session: Session<Model, Message> =
+            serde_json::from_str(json).map_err(|e| JsValue::from(e.to_string()))?;
+
+        self.replay_session(session)
+    }
+
+    pub fn replay_session(&self, session: Session<Model, Message>) -> Result<(), JsValue> {
+        if self.processing.get() {
+            return Err("can't replay while a message is being handled".into());
+        }
+
+        let mut debugger = self
+            .debug
+            .then(|| Debugger::new(session.initial_model.clone()));
+        let mut model = session.initial_model;
+
+        for message in session.messages {
+            info!("replay message: {:#?}", message);
+
+            (self.update)(message.clone(), &mut model);
+
+            if let Some(debugger) = debugger.as_mut() {
+                debugger.record(message, model.clone());
+            }
+        }
+
+        if debugger.is_some() {
+            self.debugger.replace(debugger);
+        }
+
+        self.processing.set(true);
+        self.update(model, vec![]);
+        self.processing.set(false);
+
+        Ok(())
     }
 }
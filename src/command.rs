@@ -1,8 +1,13 @@
 use async_trait::async_trait;
 
+/// A side effect that the app performs on behalf of `update`, such as a
+/// request, optionally sending a message back when it's done.
 #[async_trait(?Send)]
 pub trait Command<Message> {
-    async fn run(&self) -> Message;
+    /// Performs the command. Returns `Some(message)` to send a message back
+    /// to the app, or `None` for fire-and-forget commands, like sending to an
+    /// outbound port.
+    async fn run(&self) -> Option<Message>;
 
     fn boxed(self) -> Box<Self>
     where
@@ -35,7 +40,7 @@ struct Mapped<Message, MappedMessage> {
 
 #[async_trait(?Send)]
 impl<Message, MappedMessage> Command<MappedMessage> for Mapped<Message, MappedMessage> {
-    async fn run(&self) -> MappedMessage {
-        (self.f)(self.command.run().await)
+    async fn run(&self) -> Option<MappedMessage> {
+        self.command.run().await.map(&self.f)
     }
 }
@@ -0,0 +1,234 @@
+use crate::{app::App, root::Root};
+use log::error;
+use std::{cell::RefCell, fmt, rc::Rc};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{CustomEvent, CustomEventInit, HtmlElement, ShadowRootInit, ShadowRootMode};
+
+#[wasm_bindgen(inline_js = r#"
+export function defineCustomElement(name, observedAttributes, connected, disconnected, attributeChanged) {
+    customElements.define(name, class extends HTMLElement {
+        static get observedAttributes() {
+            return observedAttributes;
+        }
+
+        connectedCallback() {
+            connected(this);
+        }
+
+        disconnectedCallback() {
+            disconnected(this);
+        }
+
+        attributeChangedCallback(name, oldValue, newValue) {
+            attributeChanged(this, name, newValue);
+        }
+    });
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = defineCustomElement)]
+    fn define_custom_element(
+        name: &str,
+        observed_attributes: js_sys::Array,
+        connected: &JsValue,
+        disconnected: &JsValue,
+        attribute_changed: &JsValue,
+    ) -> Result<(), JsValue>;
+}
+
+type CreateFn<Model, Message> = dyn Fn(Root) -> App<Model, Message>;
+type AttributeFn<Message> = dyn Fn(Option<&str>) -> Message;
+type EventFn<Message> = dyn Fn(&Message) -> Option<(String, JsValue)>;
+type Instances<Model, Message> = Rc<RefCell<Vec<(HtmlElement, App<Model, Message>)>>>;
+
+/// Packages an app as a custom element, so that it can be used from HTML as
+/// `<name></name>`.
+///
+/// Every instance of the element gets its own app, rendered into the
+/// element's shadow root when it is connected and stopped when it is
+/// disconnected.
+pub struct CustomElement<Model, Message> {
+    name: String,
+    create: Rc<CreateFn<Model, Message>>,
+    attributes: Vec<(String, Rc<AttributeFn<Message>>)>,
+    event: Rc<EventFn<Message>>,
+}
+
+impl<Model, Message> CustomElement<Model, Message>
+where
+    Model: 'static + Clone + fmt::Debug + Eq,
+    Message: 'static + Clone + fmt::Debug,
+{
+    /// `create` builds the app for one instance of the element, mounted on
+    /// the given root.
+    pub fn new<Create>(name: &str, create: Create) -> Self
+    where
+        Create: 'static + Fn(Root) -> App<Model, Message>,
+    {
+        Self {
+            name: name.into(),
+            create: Rc::new(create),
+            attributes: vec![],
+            event: Rc::new(|_| None),
+        }
+    }
+
+    /// Observes the HTML attribute `name`, sending the app a message whenever
+    /// it is set, changed or removed.
+    pub fn attribute<Handler>(mut self, name: &str, handler: Handler) -> Self
+    where
+        Handler: 'static + Fn(Option<&str>) -> Message,
+    {
+        self.attributes.push((name.into(), Rc::new(handler)));
+        self
+    }
+
+    /// Dispatches a `CustomEvent` from the element for every message `event`
+    /// returns an event name and detail for, once the app is done handling
+    /// it.
+    pub fn event<Event>(mut self, event: Event) -> Self
+    where
+        Event: 'static + Fn(&Message) -> Option<(String, JsValue)>,
+    {
+        self.event = Rc::new(event);
+        self
+    }
+
+    /// Registers the element with the browser. Elements already in the
+    /// document are upgraded immediately.
+    pub fn define(self) -> Result<(), JsValue> {
+        let instances: Instances<Model, Message> = Rc::new(RefCell::new(vec![]));
+        let definition = Rc::new(self);
+
+        let connected = {
+            let instances = instances.clone();
+            let definition = definition.clone();
+
+            Closure::wrap(Box::new(
+                move |element: HtmlElement| match definition.connect(&element) {
+                    Ok(app) => instances.borrow_mut().push((element, app)),
+                    Err(err) => error!("failed to start {}: {:?}", definition.name, err),
+                },
+            ) as Box<dyn Fn(_)>)
+        };
+
+        let disconnected = {
+            let instances = instances.clone();
+            let name = definition.name.clone();
+
+            Closure::wrap(Box::new(move |element: HtmlElement| {
+                let app = {
+                    let mut instances = instances.borrow_mut();
+                    let index = instances
+                        .iter()
+                        .position(|(instance, _)| *instance == element);
+
+                    index.map(|index| instances.remove(index).1)
+                };
+
+                if let Some(Err(err)) = app.map(|app| app.stop()) {
+                    error!("failed to stop {}: {:?}", name, err);
+                }
+            }) as Box<dyn Fn(_)>)
+        };
+
+        let attribute_changed = {
+            let definition = definition.clone();
+
+            Closure::wrap(Box::new(
+                move |element: HtmlElement, name: String, value: Option<String>| {
+                    // attributes set before the element is connected are read
+                    // when its app starts
+                    let app = instances
+                        .borrow()
+                        .iter()
+                        .find(|(instance, _)| *instance == element)
+                        .map(|(_, app)| app.clone());
+
+                    if let (Some(app), Some(message)) =
+                        (app, definition.attribute_message(&name, value.as_deref()))
+                    {
+                        app.handle_message(message);
+                    }
+                },
+            ) as Box<dyn Fn(_, _, _)>)
+        };
+
+        let observed_attributes = definition
+            .attributes
+            .iter()
+            .map(|(name, _)| JsValue::from(name))
+            .collect();
+
+        define_custom_element(
+            &definition.name,
+            observed_attributes,
+            connected.as_ref(),
+            disconnected.as_ref(),
+            attribute_changed.as_ref(),
+        )?;
+
+        // the element can't be undefined, so its callbacks live as long as the page
+        connected.forget();
+        disconnected.forget();
+        attribute_changed.forget();
+
+        Ok(())
+    }
+
+    fn connect(&self, element: &HtmlElement) -> Result<App<Model, Message>, JsValue> {
+        let shadow_root = match element.shadow_root() {
+            Some(shadow_root) => shadow_root,
+            None => element.attach_shadow(&ShadowRootInit::new(ShadowRootMode::Open))?,
+        };
+
+        let host = element.clone();
+        let event = self.event.clone();
+        let app = (self.create)(shadow_root.into()).on_message(move |message| {
+            if let Some((name, detail)) = event(message) {
+                let host = host.clone();
+
+                // the app can't be stopped while it's handling a message, so
+                // listeners that remove the element must run afterwards
+                spawn_local(async move {
+                    if let Err(err) = dispatch_event(&host, &name, &detail) {
+                        error!("failed to dispatch {}: {:?}", name, err);
+                    }
+                });
+            }
+        });
+
+        app.start_with_flags(JsValue::UNDEFINED)?;
+
+        for (name, _) in &self.attributes {
+            if let Some(message) =
+                self.attribute_message(name, element.get_attribute(name).as_deref())
+            {
+                app.handle_message(message);
+            }
+        }
+
+        Ok(app)
+    }
+
+    fn attribute_message(&self, name: &str, value: Option<&str>) -> Option<Message> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, handler)| handler(value))
+    }
+}
+
+fn dispatch_event(element: &HtmlElement, name: &str, detail: &JsValue) -> Result<(), JsValue> {
+    let init = CustomEventInit::new();
+    init.set_bubbles(true);
+    // lets the event cross the shadow root of an enclosing custom element
+    init.set_composed(true);
+    init.set_detail(detail);
+
+    let event = CustomEvent::new_with_event_init_dict(name, &init)?;
+    element.dispatch_event(&event)?;
+
+    Ok(())
+}
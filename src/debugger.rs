@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+/// Records every message handled by an app along with the model it produced,
+/// so that earlier models can be shown again.
+pub struct Debugger<Model, Message> {
+    initial_model: Model,
+    history: Vec<(Message, Model)>,
+    // number of messages applied to the model being shown, while paused
+    paused_at: Option<usize>,
+}
+
+impl<Model, Message> Debugger<Model, Message> {
+    pub(crate) fn new(initial_model: Model) -> Self {
+        Self {
+            initial_model,
+            history: vec![],
+            paused_at: None,
+        }
+    }
+
+    pub fn initial_model(&self) -> &Model {
+        &self.initial_model
+    }
+
+    /// Every handled message, paired with the model after it was applied.
+    pub fn history(&self) -> &[(Message, Model)] {
+        &self.history
+    }
+
+    /// The model after the first `index` messages were applied, where 0 is
+    /// the initial model.
+    pub fn model_at(&self, index: usize) -> Option<&Model> {
+        match index {
+            0 => Some(&self.initial_model),
+            index => self.history.get(index - 1).map(|(_, model)| model),
+        }
+    }
+
+    pub fn paused_at(&self) -> Option<usize> {
+        self.paused_at
+    }
+
+    /// The initial model and every recorded message, which can be replayed to
+    /// reach the latest model again.
+    pub fn session(&self) -> Session<Model, Message>
+    where
+        Model: Clone,
+        Message: Clone,
+    {
+        Session {
+            initial_model: self.initial_model.clone(),
+            messages: self
+                .history
+                .iter()
+                .map(|(message, _)| message.clone())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, message: Message, model: Model) {
+        self.history.push((message, model));
+    }
+
+    // Shows the model after the first `index` messages, pausing unless it is
+    // the latest one, in which case the whole history is kept.
+    pub(crate) fn jump_to(&mut self, index: usize) -> Option<&Model> {
+        if index > self.history.len() {
+            return None;
+        }
+
+        self.paused_at = if index < self.history.len() {
+            Some(index)
+        } else {
+            None
+        };
+        self.model_at(index)
+    }
+
+    // Forgets everything after the model being shown, so that new messages are
+    // applied to it.
+    pub(crate) fn resume(&mut self) {
+        if let Some(index) = self.paused_at.take() {
+            self.history.truncate(index);
+        }
+    }
+}
+
+/// A log of the messages dispatched to an app, starting from its initial model.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Session<Model, Message> {
+    pub initial_model: Model,
+    pub messages: Vec<Message>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The initial model 0 and `count` messages, each adding 1.
+    fn debugger(count: u32) -> Debugger<u32, &'static str> {
+        let mut debugger = Debugger::new(0);
+
+        for model in 1..=count {
+            debugger.record("increment", model);
+        }
+
+        debugger
+    }
+
+    #[test]
+    fn models_are_recorded_after_each_message() {
+        let debugger = debugger(2);
+
+        assert_eq!(debugger.model_at(0), Some(&0));
+        assert_eq!(debugger.model_at(2), Some(&2));
+        assert_eq!(debugger.model_at(3), None);
+        assert_eq!(
+            debugger.session(),
+            Session {
+                initial_model: 0,
+                messages: vec!["increment", "increment"],
+            }
+        );
+    }
+
+    #[test]
+    fn jumping_back_pauses() {
+        let mut debugger = debugger(5);
+
+        assert_eq!(debugger.jump_to(2), Some(&2));
+        assert_eq!(debugger.paused_at(), Some(2));
+    }
+
+    #[test]
+    fn resuming_discards_the_history_after_the_model_shown() {
+        let mut debugger = debugger(5);
+
+        debugger.jump_to(2);
+        debugger.resume();
+
+        assert_eq!(debugger.paused_at(), None);
+        assert_eq!(debugger.history().len(), 2);
+    }
+
+    #[test]
+    fn jumping_to_the_latest_model_keeps_the_history() {
+        let mut debugger = debugger(5);
+
+        debugger.jump_to(2);
+        assert_eq!(debugger.jump_to(5), Some(&5));
+        assert_eq!(debugger.paused_at(), None);
+
+        debugger.resume();
+        debugger.record("increment", 6);
+
+        assert_eq!(debugger.history().len(), 6);
+        assert_eq!(debugger.model_at(6), Some(&6));
+    }
+
+    #[test]
+    fn jumping_past_the_history_does_nothing() {
+        let mut debugger = debugger(2);
+
+        debugger.jump_to(1);
+
+        assert_eq!(debugger.jump_to(3), None);
+        assert_eq!(debugger.paused_at(), Some(1));
+    }
+}
@@ -0,0 +1,215 @@
+//! Decoders turn JS values, like event objects, into Rust values, failing
+//! with a description of what was expected instead of panicking.
+
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+
+type DecodeFn<T> = dyn Fn(&JsValue) -> Result<T, String>;
+
+pub struct Decoder<T>(Rc<DecodeFn<T>>);
+
+impl<T> Clone for Decoder<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Decoder<T>
+where
+    T: 'static,
+{
+    pub fn new<F>(f: F) -> Self
+    where
+        F: 'static + Fn(&JsValue) -> Result<T, String>,
+    {
+        Self(Rc::new(f))
+    }
+
+    pub fn decode(&self, value: &JsValue) -> Result<T, String> {
+        (self.0)(value)
+    }
+
+    pub fn map<U, F>(self, f: F) -> Decoder<U>
+    where
+        U: 'static,
+        F: 'static + Fn(T) -> U,
+    {
+        Decoder::new(move |value| self.decode(value).map(&f))
+    }
+
+    /// Decodes with the decoder `f` returns for the result of this one.
+    pub fn and_then<U, F>(self, f: F) -> Decoder<U>
+    where
+        U: 'static,
+        F: 'static + Fn(T) -> Decoder<U>,
+    {
+        Decoder::new(move |value| f(self.decode(value)?).decode(value))
+    }
+}
+
+/// Always succeeds with `value`, ignoring the JS value.
+pub fn succeed<T>(value: T) -> Decoder<T>
+where
+    T: 'static + Clone,
+{
+    Decoder::new(move |_| Ok(value.clone()))
+}
+
+/// Always fails with `message`.
+pub fn fail<T>(message: &str) -> Decoder<T>
+where
+    T: 'static,
+{
+    let message = message.to_string();
+    Decoder::new(move |_| Err(message.clone()))
+}
+
+/// The JS value itself.
+pub fn value() -> Decoder<JsValue> {
+    Decoder::new(|value| Ok(value.clone()))
+}
+
+pub fn string() -> Decoder<String> {
+    Decoder::new(|value| {
+        value
+            .as_string()
+            .ok_or_else(|| format!("expected a string, got {:?}", value))
+    })
+}
+
+pub fn bool() -> Decoder<bool> {
+    Decoder::new(|value| {
+        value
+            .as_bool()
+            .ok_or_else(|| format!("expected a bool, got {:?}", value))
+    })
+}
+
+pub fn f64() -> Decoder<f64> {
+    Decoder::new(|value| {
+        value
+            .as_f64()
+            .ok_or_else(|| format!("expected a number, got {:?}", value))
+    })
+}
+
+pub fn i32() -> Decoder<i32> {
+    f64().and_then(|number| {
+        if number.fract() == 0.0 && number >= i32::MIN.into() && number <= i32::MAX.into() {
+            succeed(number as i32)
+        } else {
+            fail(&format!("expected an integer, got {}", number))
+        }
+    })
+}
+
+/// Decodes the property `name` of an object, including properties defined by
+/// getters, like those of DOM events.
+pub fn field<T>(name: &str, decoder: Decoder<T>) -> Decoder<T>
+where
+    T: 'static,
+{
+    let name = name.to_string();
+
+    Decoder::new(move |value| {
+        if !value.is_object() {
+            return Err(format!(
+                "expected an object with field {:?}, got {:?}",
+                name, value
+            ));
+        }
+
+        let field = js_sys::Reflect::get(value, &JsValue::from(&name))
+            .map_err(|err| format!("failed to get field {:?}: {:?}", name, err))?;
+
+        decoder
+            .decode(&field)
+            .map_err(|err| format!("at field {:?}: {}", name, err))
+    })
+}
+
+/// Decodes the value at a path of nested properties, such as
+/// `["target", "value"]`.
+pub fn at<T>(path: &[&str], decoder: Decoder<T>) -> Decoder<T>
+where
+    T: 'static,
+{
+    path.iter()
+        .rev()
+        .fold(decoder, |decoder, name| field(name, decoder))
+}
+
+/// Tries each decoder in order, succeeding with the first that does.
+pub fn one_of<T>(decoders: Vec<Decoder<T>>) -> Decoder<T>
+where
+    T: 'static,
+{
+    Decoder::new(move |value| {
+        let mut errors = vec![];
+
+        for decoder in &decoders {
+            match decoder.decode(value) {
+                Ok(result) => return Ok(result),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        Err(format!(
+            "none of the decoders succeeded: {}",
+            errors.join("; ")
+        ))
+    })
+}
+
+/// Decodes `null` or `undefined` as `None`.
+pub fn nullable<T>(decoder: Decoder<T>) -> Decoder<Option<T>>
+where
+    T: 'static,
+{
+    Decoder::new(move |value| {
+        if value.is_null() || value.is_undefined() {
+            Ok(None)
+        } else {
+            decoder.decode(value).map(Some)
+        }
+    })
+}
+
+pub fn map2<A, B, T, F>(a: Decoder<A>, b: Decoder<B>, f: F) -> Decoder<T>
+where
+    A: 'static,
+    B: 'static,
+    T: 'static,
+    F: 'static + Fn(A, B) -> T,
+{
+    Decoder::new(move |value| Ok(f(a.decode(value)?, b.decode(value)?)))
+}
+
+// Event payloads
+
+/// `event.target.value`, as set by inputs, text areas and selects.
+pub fn target_value() -> Decoder<String> {
+    at(&["target", "value"], string())
+}
+
+/// `event.target.checked`, as set by checkboxes and radio buttons.
+pub fn target_checked() -> Decoder<bool> {
+    at(&["target", "checked"], bool())
+}
+
+/// `event.key` of a keyboard event.
+pub fn key() -> Decoder<String> {
+    field("key", string())
+}
+
+/// `event.clientX` and `event.clientY` of a mouse or pointer event.
+pub fn client_position() -> Decoder<(f64, f64)> {
+    map2(field("clientX", f64()), field("clientY", f64()), |x, y| {
+        (x, y)
+    })
+}
+
+/// `event.dataTransfer` of a drag event.
+pub fn data_transfer() -> Decoder<JsValue> {
+    field("dataTransfer", value())
+}
@@ -0,0 +1,345 @@
+//! Event delegation. Instead of adding a listener to every element with an
+//! event handler, the app listens once per event type at its root and looks
+//! the handlers of the event's target and its ancestors up in a table keyed by
+//! node, which the renderer keeps up to date. Re-rendering only swaps the
+//! decoders in the table, and removed nodes drop theirs.
+//!
+//! Since every DOM listener is on the root, an event's `currentTarget` is the
+//! root rather than the element whose handler decodes it.
+//!
+//! Apps can be mounted inside each other's views, so each delegation keeps
+//! its own ids, and leaves the events of nodes under a nested app's root to
+//! that app.
+
+use crate::{
+    decode::Decoder,
+    subscription::Listener,
+    virtual_dom::{self, Handled, Options},
+};
+use log::trace;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{AddEventListenerOptions, Node};
+
+// The roots of every delegation on the page, to tell where a nested app's
+// nodes start.
+thread_local! {
+    static ROOTS: js_sys::WeakSet = js_sys::WeakSet::new();
+}
+
+pub(crate) struct Delegation<Message> {
+    root: Node,
+    table: Rc<Table<Message>>,
+    next_id: Cell<u32>,
+    // capture and bubble listeners at the root, by event type and passiveness
+    listeners: RefCell<HashMap<(String, bool), [Listener; 2]>>,
+}
+
+struct Table<Message> {
+    // the ids of the DOM elements with handlers, by element
+    ids: js_sys::WeakMap,
+    handlers: RefCell<HashMap<u32, Vec<Rc<Handler<Message>>>>>,
+    // the `once` handlers that have run, by node, event type and phase, so
+    // that re-rendering doesn't bring them back
+    spent: RefCell<HashSet<(u32, String, bool)>>,
+    dispatch: Box<dyn Fn(Message)>,
+}
+
+struct Handler<Message> {
+    name: String,
+    options: Options,
+    decoder: Decoder<Handled<Message>>,
+}
+
+impl<Message> Delegation<Message>
+where
+    Message: 'static,
+{
+    /// Delegates the events of everything rendered under `root`, sending their
+    /// messages to `dispatch`.
+    pub fn new<Dispatch>(root: Node, dispatch: Dispatch) -> Self
+    where
+        Dispatch: 'static + Fn(Message),
+    {
+        ROOTS.with(|roots| roots.add(root.unchecked_ref()));
+
+        Self {
+            root,
+            table: Rc::new(Table {
+                ids: js_sys::WeakMap::new(),
+                handlers: RefCell::new(HashMap::new()),
+                spent: RefCell::new(HashSet::new()),
+                dispatch: Box::new(dispatch),
+            }),
+            next_id: Cell::new(0),
+            listeners: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Makes the handlers of `element` the ones of `dom_element`.
+    pub fn set_handlers(
+        &self,
+        element: &virtual_dom::Element<Message>,
+        dom_element: &web_sys::Element,
+    ) -> Result<(), JsValue> {
+        let handlers: Vec<_> = element
+            .attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                virtual_dom::Attribute::On(event) => Some(Rc::new(Handler {
+                    name: event.name.clone(),
+                    options: event.options,
+                    decoder: event.decoder.clone(),
+                })),
+                _ => None,
+            })
+            .collect();
+
+        let id = match (self.table.id(dom_element), handlers.is_empty()) {
+            (Some(id), _) => id,
+            (None, true) => return Ok(()),
+            (None, false) => {
+                let id = self.next_id.get();
+                self.next_id.set(id + 1);
+                self.table.ids.set(dom_element, &id.into());
+                id
+            }
+        };
+
+        // a spent handler stays spent for as long as the view keeps it
+        let handlers: Vec<_> = {
+            let mut spent = self.table.spent.borrow_mut();
+            spent.retain(|(spent_id, name, capture)| {
+                *spent_id != id
+                    || handlers.iter().any(|handler| {
+                        handler.options.once
+                            && handler.name == *name
+                            && handler.options.capture == *capture
+                    })
+            });
+
+            handlers
+                .into_iter()
+                .filter(|handler| {
+                    !handler.options.once
+                        || !spent.contains(&(id, handler.name.clone(), handler.options.capture))
+                })
+                .collect()
+        };
+
+        for handler in &handlers {
+            self.listen(&handler.name, handler.options.passive)?;
+        }
+
+        let mut table = self.table.handlers.borrow_mut();
+        if handlers.is_empty() {
+            table.remove(&id);
+        } else {
+            table.insert(id, handlers);
+        }
+
+        Ok(())
+    }
+
+    /// Drops the handlers of `dom_node` and everything under it, once it has
+    /// been removed from the DOM.
+    pub fn forget(&self, dom_node: &Node) {
+        if let Some(id) = self.table.id(dom_node) {
+            self.table.ids.delete(dom_node);
+            self.table.handlers.borrow_mut().remove(&id);
+            self.table
+                .spent
+                .borrow_mut()
+                .retain(|(spent_id, _, _)| *spent_id != id);
+        }
+
+        let mut child = dom_node.first_child();
+        while let Some(node) = child {
+            self.forget(&node);
+            child = node.next_sibling();
+        }
+    }
+
+    // Makes sure there are listeners for `event_type` at the root.
+    fn listen(&self, event_type: &str, passive: bool) -> Result<(), JsValue> {
+        let key = (event_type.to_string(), passive);
+
+        if self.listeners.borrow().contains_key(&key) {
+            return Ok(());
+        }
+
+        trace!("delegate {:?} events", event_type);
+
+        let listeners = [
+            self.add_listener(event_type, passive, true)?,
+            self.add_listener(event_type, passive, false)?,
+        ];
+        self.listeners.borrow_mut().insert(key, listeners);
+
+        Ok(())
+    }
+
+    fn add_listener(
+        &self,
+        event_type: &str,
+        passive: bool,
+        capture: bool,
+    ) -> Result<Listener, JsValue> {
+        let root = self.root.clone();
+        let table = self.table.clone();
+
+        let callback = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            table.handle(&root, &event, passive, capture);
+        }) as Box<dyn Fn(_)>);
+
+        let options = AddEventListenerOptions::new();
+        options.set_passive(passive);
+        options.set_capture(capture);
+
+        self.root
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                event_type,
+                callback.as_ref().unchecked_ref(),
+                &options,
+            )?;
+
+        let root = self.root.clone();
+        let event_type = event_type.to_string();
+
+        Ok(Listener::new(move || {
+            root.remove_event_listener_with_callback_and_bool(
+                &event_type,
+                callback.as_ref().unchecked_ref(),
+                capture,
+            )
+            .unwrap();
+        }))
+    }
+}
+
+impl<Message> Table<Message>
+where
+    Message: 'static,
+{
+    // Runs the handlers for the phase of `event` the root is listening to.
+    // Capture handlers run from the root down to the target, and the others
+    // from the target up to the root, or on the target only for events that
+    // don't bubble, which the root only sees while capturing.
+    fn handle(&self, root: &Node, event: &web_sys::Event, passive: bool, capture: bool) {
+        let path = self.path(root, event);
+
+        if capture {
+            for id in path.iter().rev().flatten() {
+                if self.run(*id, event, passive, true) {
+                    return;
+                }
+            }
+
+            if !event.bubbles() {
+                if let Some(Some(id)) = path.first() {
+                    self.run(*id, event, passive, false);
+                }
+            }
+        } else if event.bubbles() {
+            for id in path.iter().flatten() {
+                if self.run(*id, event, passive, false) {
+                    return;
+                }
+            }
+        }
+    }
+
+    // The ids of the event's target and its ancestors below the root, if they
+    // have any, leaving out the nodes of apps nested in this one.
+    fn path(&self, root: &Node, event: &web_sys::Event) -> Vec<Option<u32>> {
+        let mut path = vec![];
+        let mut node = event
+            .target()
+            .and_then(|target| target.dyn_into::<Node>().ok());
+
+        while let Some(current) = node {
+            if current.is_same_node(Some(root)) {
+                break;
+            }
+
+            // the nested app handles the events of what it rendered itself
+            if ROOTS.with(|roots| roots.has(current.unchecked_ref())) {
+                path.clear();
+            }
+
+            path.push(self.id(&current));
+            node = current.parent_node();
+        }
+
+        path
+    }
+
+    // Runs the matching handlers of the node `id`, returning whether one of
+    // them stopped the event propagating.
+    fn run(&self, id: u32, event: &web_sys::Event, passive: bool, capture: bool) -> bool {
+        // cloned, since dispatching can re-render and replace the handlers
+        let handlers: Vec<_> = match self.handlers.borrow().get(&id) {
+            Some(handlers) => handlers
+                .iter()
+                .filter(|handler| {
+                    handler.name == event.type_()
+                        && handler.options.passive == passive
+                        && handler.options.capture == capture
+                })
+                .cloned()
+                .collect(),
+            None => return false,
+        };
+
+        let mut stopped = false;
+
+        for handler in handlers {
+            if handler.options.once {
+                if let Some(handlers) = self.handlers.borrow_mut().get_mut(&id) {
+                    handlers.retain(|other| !Rc::ptr_eq(other, &handler));
+                }
+
+                self.spent
+                    .borrow_mut()
+                    .insert((id, handler.name.clone(), handler.options.capture));
+            }
+
+            let handled = match handler.decoder.decode(event) {
+                Ok(handled) => handled,
+                Err(err) => {
+                    trace!("ignore {:?} event: {}", handler.name, err);
+                    continue;
+                }
+            };
+
+            if handled.stop_propagation {
+                event.stop_propagation();
+                stopped = true;
+            }
+
+            if handled.prevent_default {
+                event.prevent_default();
+            }
+
+            if let Some(message) = handled.message {
+                (self.dispatch)(message);
+            }
+        }
+
+        stopped
+    }
+
+    fn id(&self, node: &Node) -> Option<u32> {
+        self.ids.get(node).as_f64().map(|id| id as u32)
+    }
+}
+
+impl<Message> Drop for Delegation<Message> {
+    fn drop(&mut self) {
+        ROOTS.with(|roots| roots.delete(self.root.unchecked_ref()));
+    }
+}
@@ -0,0 +1,181 @@
+//! Static site export: renders an app's first view for each of its routes to
+//! an HTML file, with the initial model embedded next to it, so that the pages
+//! can be served as they are and hydrated once the app starts. Exporting runs
+//! natively, for example from a build script or a small binary.
+//!
+//! On the page, the app picks the model up as its flags and, with `hydrate`,
+//! adopts the exported markup instead of rendering over it:
+//!
+//! ```ignore
+//! let app = App::with_flags(|model: Model| (model, vec![]), update, view, "app").hydrate();
+//! app.start_with_flags(export::read_model("app")?)?;
+//! ```
+
+use crate::{command::Commands, virtual_dom::Html};
+use serde::Serialize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use wasm_bindgen::JsValue;
+
+const PLACEHOLDER: &str = "{{app}}";
+
+const DEFAULT_TEMPLATE: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+</head>
+<body>
+{{app}}
+</body>
+</html>
+";
+
+type InitFn<Model, Message> = dyn Fn(&str) -> (Model, Commands<Message>);
+type ViewFn<Model, Message> = dyn Fn(&Model) -> Html<Message>;
+
+pub struct Export<Model, Message> {
+    init: Box<InitFn<Model, Message>>,
+    view: Box<ViewFn<Model, Message>>,
+    routes: Vec<String>,
+    root_id: String,
+    template: String,
+}
+
+impl<Model, Message> Export<Model, Message>
+where
+    Model: Serialize,
+{
+    /// Exports the views of `init`'s model for each route, which `init` is
+    /// given. Its commands aren't run.
+    pub fn new<Init, View>(init: Init, view: View) -> Self
+    where
+        Init: 'static + Fn(&str) -> (Model, Commands<Message>),
+        View: 'static + Fn(&Model) -> Html<Message>,
+    {
+        Self {
+            init: Box::new(init),
+            view: Box::new(view),
+            routes: vec![],
+            root_id: "app".into(),
+            template: DEFAULT_TEMPLATE.into(),
+        }
+    }
+
+    /// Adds a route, such as `/about`, which is written to `about/index.html`.
+    /// Only `/` is exported if no routes are added.
+    pub fn route(mut self, route: &str) -> Self {
+        self.routes.push(route.into());
+        self
+    }
+
+    pub fn routes<Routes>(mut self, routes: Routes) -> Self
+    where
+        Routes: IntoIterator,
+        Routes::Item: AsRef<str>,
+    {
+        self.routes
+            .extend(routes.into_iter().map(|route| route.as_ref().to_string()));
+        self
+    }
+
+    /// The id of the element the view is rendered into, which the app is
+    /// mounted on when it starts. Defaults to `app`.
+    pub fn root_id(mut self, root_id: &str) -> Self {
+        self.root_id = root_id.into();
+        self
+    }
+
+    /// The page around the app, which must contain `{{app}}` where the root
+    /// element goes, along with the scripts that start the app.
+    pub fn template(mut self, template: &str) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Renders the page for `route`.
+    pub fn render(&self, route: &str) -> io::Result<String> {
+        if !self.template.contains(PLACEHOLDER) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("template has no {}", PLACEHOLDER),
+            ));
+        }
+
+        let (model, _) = (self.init)(route);
+        let html = (self.view)(&model).to_html_string();
+        // `<` only occurs in strings, where it can be escaped so that the
+        // script can't be ended early
+        let json = serde_json::to_string(&model)?.replace('<', "\\u003c");
+
+        let app = format!(
+            "<div id=\"{id}\">{html}</div>\n<script type=\"application/json\" id=\"{id}-model\">{json}</script>",
+            id = self.root_id,
+            html = html,
+            json = json,
+        );
+
+        Ok(self.template.replace(PLACEHOLDER, &app))
+    }
+
+    /// Writes the page of every route under `directory`, returning the paths
+    /// of the files.
+    pub fn write<P>(&self, directory: P) -> io::Result<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        let default_routes = ["/".to_string()];
+        let routes = if self.routes.is_empty() {
+            &default_routes[..]
+        } else {
+            &self.routes[..]
+        };
+
+        routes
+            .iter()
+            .map(|route| {
+                let path = directory.as_ref().join(route_path(route)?);
+
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, self.render(route)?)?;
+
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+// The file a route is written to, relative to the export directory.
+fn route_path(route: &str) -> io::Result<PathBuf> {
+    let mut path = PathBuf::new();
+
+    for segment in route.split('/').filter(|segment| !segment.is_empty()) {
+        if segment == "." || segment == ".." {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid route {:?}", route),
+            ));
+        }
+
+        path.push(segment);
+    }
+
+    path.push("index.html");
+
+    Ok(path)
+}
+
+/// Reads the model an exported page embeds for the app mounted on `root_id`,
+/// to be passed to `App::start_with_flags`.
+pub fn read_model(root_id: &str) -> Result<JsValue, JsValue> {
+    let id = format!("{}-model", root_id);
+    let script = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(&id))
+        .ok_or_else(|| JsValue::from(format!("no element with id {:?}", id)))?;
+
+    js_sys::JSON::parse(&script.text_content().unwrap_or_default())
+}
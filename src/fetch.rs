@@ -1,4 +1,4 @@
-use crate::command::Command;
+use crate::{command::Command, json};
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 use wasm_bindgen::{prelude::*, JsCast};
@@ -25,9 +25,9 @@ where
     }
 
     async fn perform(&self) -> Result<Data, JsValue> {
-        let mut opts = RequestInit::new();
-        opts.method("GET");
-        opts.mode(RequestMode::Cors);
+        let opts = RequestInit::new();
+        opts.set_method("GET");
+        opts.set_mode(RequestMode::Cors);
 
         let request = Request::new_with_str_and_init(&self.url, &opts).unwrap();
 
@@ -38,7 +38,7 @@ where
             .dyn_into()?;
         let json = JsFuture::from(response.json()?).await?;
 
-        json.into_serde().map_err(|e| e.to_string().into())
+        json::from_js_value(&json)
     }
 }
 
@@ -47,8 +47,8 @@ impl<Data, Message> Command<Message> for Fetch<Data, Message>
 where
     Data: DeserializeOwned,
 {
-    async fn run(&self) -> Message {
+    async fn run(&self) -> Option<Message> {
         let result = self.perform().await;
-        (self.handler)(result)
+        Some((self.handler)(result))
     }
 }
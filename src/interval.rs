@@ -0,0 +1,49 @@
+use crate::subscription::{Listener, Sink, Subscription};
+use wasm_bindgen::{prelude::*, JsCast};
+
+pub struct Interval<Message> {
+    millis: i32,
+    handler: Box<dyn Fn(f64) -> Message>,
+}
+
+impl<Message> Interval<Message> {
+    /// Sends a message every `millis` milliseconds. The handler receives the
+    /// current time in milliseconds since the epoch.
+    pub fn new<Handler>(millis: i32, handler: Handler) -> Self
+    where
+        Handler: 'static + Fn(f64) -> Message,
+    {
+        Self {
+            millis,
+            handler: Box::new(handler),
+        }
+    }
+}
+
+impl<Message> Subscription<Message> for Interval<Message> {
+    fn id(&self) -> String {
+        format!("interval:{}", self.millis)
+    }
+
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue> {
+        let window = web_sys::window().unwrap();
+
+        let callback = Closure::wrap(Box::new(move || {
+            sink.send(js_sys::Date::now().into());
+        }) as Box<dyn Fn()>);
+
+        let handle = window.set_interval_with_callback_and_timeout_and_arguments_0(
+            callback.as_ref().unchecked_ref(),
+            self.millis,
+        )?;
+
+        Ok(Listener::new(move || {
+            window.clear_interval_with_handle(handle);
+            drop(callback);
+        }))
+    }
+
+    fn message(&self, value: JsValue) -> Option<Message> {
+        value.as_f64().map(&self.handler)
+    }
+}
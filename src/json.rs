@@ -0,0 +1,22 @@
+use serde::de::DeserializeOwned;
+use wasm_bindgen::JsValue;
+
+/// Deserializes a JS value by way of its JSON representation. `undefined` is
+/// treated like `null`.
+pub fn from_js_value<T>(value: &JsValue) -> Result<T, JsValue>
+where
+    T: DeserializeOwned,
+{
+    let json = if value.is_undefined() {
+        "null".into()
+    } else {
+        String::from(js_sys::JSON::stringify(value)?)
+    };
+
+    serde_json::from_str(&json).map_err(|e| e.to_string().into())
+}
+
+/// Converts a JSON value to the equivalent JS value.
+pub fn to_js_value(value: &serde_json::Value) -> Result<JsValue, JsValue> {
+    js_sys::JSON::parse(&value.to_string())
+}
@@ -1,10 +1,69 @@
+// lets the paths the macros expand to work inside this crate too
+extern crate self as rust_elm_architecture;
+
 pub mod app;
 pub mod command;
+pub mod custom_element;
+pub mod debugger;
+pub mod decode;
+mod delegation;
+pub mod export;
 pub mod fetch;
+pub mod interval;
+pub mod json;
+pub mod port;
 pub mod renderer;
+pub mod root;
+pub mod subscription;
+pub mod typed;
 pub mod virtual_dom;
+pub mod window;
 
 pub use app::App;
 pub use command::{Command, Commands};
+pub use custom_element::CustomElement;
+pub use decode::Decoder;
+pub use export::Export;
 pub use fetch::Fetch;
+pub use interval::Interval;
+/// JSX-like markup for views; see the `macros` crate for the syntax. Tags and
+/// attributes are checked at compile time:
+///
+/// ```compile_fail
+/// use rust_elm_architecture::{html, Html};
+///
+/// let view: Html<()> = html! { <blink>"unknown element"</blink> };
+/// ```
+///
+/// ```compile_fail
+/// use rust_elm_architecture::{html, Html};
+///
+/// let view: Html<()> = html! { <div colour="red" /> };
+/// ```
+///
+/// ```compile_fail
+/// use rust_elm_architecture::{html, Html};
+///
+/// let view: Html<()> = html! { <div><p></div> };
+/// ```
+pub use macros::html;
+/// Views from HTML files; see the `macros` crate for the syntax. The markup
+/// is checked at compile time:
+///
+/// ```compile_fail
+/// use rust_elm_architecture::{include_html, Html};
+///
+/// // `<p>` is never closed
+/// let view: Html<()> = include_html!("tests/templates/unclosed.html");
+/// ```
+///
+/// ```compile_fail
+/// use rust_elm_architecture::{include_html, Html};
+///
+/// // `onclick="save"` has no binding
+/// let view: Html<()> = include_html!("tests/templates/button.html");
+/// ```
+pub use macros::include_html;
+pub use root::Root;
+pub use subscription::{Subs, Subscription};
 pub use virtual_dom::{Attribute, Html};
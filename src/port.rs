@@ -0,0 +1,188 @@
+use crate::{
+    command::Command,
+    json,
+    subscription::{Listener, Sink, Subscription},
+};
+use async_trait::async_trait;
+use log::{error, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{cell::RefCell, collections::HashMap, marker::PhantomData};
+use wasm_bindgen::prelude::*;
+
+// Ports are shared by every app on the page, like the functions JavaScript
+// uses to talk to them.
+thread_local! {
+    static PORTS: RefCell<Ports> = RefCell::new(Ports::default());
+}
+
+#[derive(Default)]
+struct Ports {
+    next_id: usize,
+    inbound: HashMap<String, Vec<(usize, Sink)>>,
+    outbound: HashMap<String, Vec<js_sys::Function>>,
+}
+
+/// Sends `value` from JavaScript to every app subscribed to the inbound port
+/// `name`.
+#[wasm_bindgen(js_name = sendToPort)]
+pub fn send_to_port(name: &str, value: JsValue) {
+    let sinks: Vec<Sink> = PORTS.with(|ports| {
+        ports
+            .borrow()
+            .inbound
+            .get(name)
+            .map(|sinks| sinks.iter().map(|(_, sink)| sink.clone()).collect())
+            .unwrap_or_default()
+    });
+
+    if sinks.is_empty() {
+        warn!("nothing is subscribed to port {:?}", name);
+    }
+
+    for sink in sinks {
+        sink.send(value.clone());
+    }
+}
+
+/// Calls `callback` with every value sent to the outbound port `name`.
+#[wasm_bindgen(js_name = subscribeToPort)]
+pub fn subscribe_to_port(name: &str, callback: js_sys::Function) {
+    PORTS.with(|ports| {
+        ports
+            .borrow_mut()
+            .outbound
+            .entry(name.into())
+            .or_default()
+            .push(callback)
+    });
+}
+
+#[wasm_bindgen(js_name = unsubscribeFromPort)]
+pub fn unsubscribe_from_port(name: &str, callback: js_sys::Function) {
+    PORTS.with(|ports| {
+        if let Some(callbacks) = ports.borrow_mut().outbound.get_mut(name) {
+            callbacks.retain(|existing| *existing != callback);
+        }
+    });
+}
+
+/// A subscription to values sent from JavaScript with `sendToPort`.
+pub struct Inbound<Value, Message> {
+    name: String,
+    handler: Box<dyn Fn(Value) -> Message>,
+}
+
+impl<Value, Message> Inbound<Value, Message>
+where
+    Value: DeserializeOwned,
+{
+    pub fn new<Handler>(name: &str, handler: Handler) -> Self
+    where
+        Handler: 'static + Fn(Value) -> Message,
+    {
+        Self {
+            name: name.into(),
+            handler: Box::new(handler),
+        }
+    }
+}
+
+impl<Value, Message> Subscription<Message> for Inbound<Value, Message>
+where
+    Value: DeserializeOwned,
+{
+    fn id(&self) -> String {
+        format!("port:{}", self.name)
+    }
+
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue> {
+        let name = self.name.clone();
+
+        let id = PORTS.with(|ports| {
+            let mut ports = ports.borrow_mut();
+
+            let id = ports.next_id;
+            ports.next_id += 1;
+            ports
+                .inbound
+                .entry(name.clone())
+                .or_default()
+                .push((id, sink));
+
+            id
+        });
+
+        Ok(Listener::new(move || {
+            PORTS.with(|ports| {
+                if let Some(sinks) = ports.borrow_mut().inbound.get_mut(&name) {
+                    sinks.retain(|(existing, _)| *existing != id);
+                }
+            })
+        }))
+    }
+
+    fn message(&self, value: JsValue) -> Option<Message> {
+        match json::from_js_value(&value) {
+            Ok(value) => Some((self.handler)(value)),
+            Err(err) => {
+                error!(
+                    "failed to decode value sent to port {:?}: {:?}",
+                    self.name, err
+                );
+                None
+            }
+        }
+    }
+}
+
+/// A command sending a value to the JavaScript callbacks subscribed to the
+/// outbound port with `subscribeToPort`.
+pub struct Outbound<Message> {
+    name: String,
+    json: Result<String, String>,
+    message: PhantomData<Message>,
+}
+
+impl<Message> Outbound<Message> {
+    pub fn new<Value>(name: &str, value: &Value) -> Self
+    where
+        Value: Serialize + ?Sized,
+    {
+        Self {
+            name: name.into(),
+            json: serde_json::to_string(value).map_err(|e| e.to_string()),
+            message: PhantomData,
+        }
+    }
+
+    fn send(&self) -> Result<(), JsValue> {
+        let json = self.json.as_ref().map_err(JsValue::from)?;
+        let value = js_sys::JSON::parse(json)?;
+
+        let callbacks = PORTS.with(|ports| {
+            ports
+                .borrow()
+                .outbound
+                .get(&self.name)
+                .cloned()
+                .unwrap_or_default()
+        });
+
+        for callback in callbacks {
+            callback.call1(&JsValue::NULL, &value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl<Message> Command<Message> for Outbound<Message> {
+    async fn run(&self) -> Option<Message> {
+        if let Err(err) = self.send() {
+            error!("failed to send to port {:?}: {:?}", self.name, err);
+        }
+
+        None
+    }
+}
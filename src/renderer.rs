@@ -1,130 +1,325 @@
 use crate::{
-    app::App,
-    virtual_dom::{self, Html},
+    delegation::Delegation,
+    json,
+    virtual_dom::{self, diff, html_string, Html, Patch},
 };
-use log::trace;
+use log::{trace, warn};
 use std::fmt;
-use wasm_bindgen::{prelude::*, JsCast, JsValue};
-use web_sys::{Document, Element, HtmlElement, InputEvent, Node, Text};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Document, HtmlElement, Node, Text};
 
-pub struct Renderer<Model, Message> {
-    app: App<Model, Message>,
+pub struct Renderer<'a, Message> {
+    delegation: &'a Delegation<Message>,
     document: Document,
 }
 
-impl<Model, Message> Renderer<Model, Message>
+impl<'a, Message> Renderer<'a, Message>
 where
-    Model: 'static + Clone + fmt::Debug + Eq,
     Message: 'static + Clone + fmt::Debug,
 {
-    pub fn new(app: &App<Model, Message>) -> Self {
+    pub(crate) fn new(delegation: &'a Delegation<Message>) -> Self {
         Self {
-            app: app.clone(),
+            delegation,
             document: web_sys::window().unwrap().document().unwrap(),
         }
     }
 
+    /// Renders `new` as the first child of `root`, patching what was rendered
+    /// from `old` if there is one.
     pub fn render(
         &self,
         old: Option<&Html<Message>>,
         new: &Html<Message>,
-        root_id: &str,
-    ) -> Result<(), JsValue>
-    where
-        Message: 'static + Clone + fmt::Debug,
-    {
-        let root = self.document.get_element_by_id(root_id).unwrap();
-        self.render_node(&old, &Some(new), &root, 0)?;
+        root: &Node,
+    ) -> Result<(), JsValue> {
+        let old = match old {
+            Some(old) => old,
+            None => {
+                trace!("insert new node: {:?}", new);
+                root.append_child(&self.create_node(new)?)?;
+
+                return Ok(());
+            }
+        };
+
+        let dom_node = Self::get_child(root, 0)?;
+
+        for patch in virtual_dom::diff(old, new) {
+            trace!("apply {:?}", patch);
+            self.apply(&patch, &dom_node)?;
+        }
 
         Ok(())
     }
 
-    fn render_node(
-        &self,
-        old: &Option<&Html<Message>>,
-        new: &Option<&Html<Message>>,
-        parent: &Element,
-        index: u32,
-    ) -> Result<(), JsValue>
-    where
-        Message: 'static + Clone + fmt::Debug,
-    {
-        match (old, new) {
-            (None, None) => panic!("don't call render_element with no old or new html"),
-            // remove old element
-            (Some(old), None) => {
-                trace!("remove old element: {:?} {}", old, index);
-                Self::remove_child(parent, index)?
-            }
-            // insert new element
-            (None, Some(new)) => {
-                trace!("insert new element: {:?}", new);
-                Self::append_child(parent, &self.create_node(new)?)?
+    /// Adopts the DOM under `root`, such as server-rendered markup, as the
+    /// rendering of `new`. Matching nodes are kept and given their event
+    /// handlers; anything that doesn't match is logged and repaired.
+    pub fn hydrate(&self, new: &Html<Message>, root: &Node) -> Result<(), JsValue> {
+        self.hydrate_children(&[new], root)
+    }
+
+    fn hydrate_children(&self, children: &[&Html<Message>], parent: &Node) -> Result<(), JsValue> {
+        let mut dom_node = parent.first_child();
+
+        for child in children {
+            if !Self::is_text(child) {
+                dom_node = Self::remove_formatting(dom_node, parent)?;
             }
-            // leave text unchanged
-            (Some(virtual_dom::Node::Text(old_text)), Some(virtual_dom::Node::Text(new_text)))
-                if old_text == new_text =>
+
+            let node = self.hydrate_node(child, dom_node.as_ref(), parent)?;
+            dom_node = node.next_sibling();
+        }
+
+        dom_node = Self::remove_formatting(dom_node, parent)?;
+
+        while let Some(node) = dom_node {
+            dom_node = node.next_sibling();
+
+            warn!("hydration: removing extra node {:?}", node.node_name());
+            parent.remove_child(&node)?;
+        }
+
+        Ok(())
+    }
+
+    // Removes the whitespace-only text nodes from `dom_node` on, which
+    // indented markup has where the view has no text, returning the first
+    // node after them.
+    fn remove_formatting(
+        mut dom_node: Option<Node>,
+        parent: &Node,
+    ) -> Result<Option<Node>, JsValue> {
+        while let Some(node) = dom_node {
+            if node.node_type() != Node::TEXT_NODE
+                || !node.text_content().unwrap_or_default().trim().is_empty()
             {
-                trace!("leave text unchanged: {:?}", new_text);
+                return Ok(Some(node));
             }
-            // update text
-            (Some(virtual_dom::Node::Text(old_text)), Some(virtual_dom::Node::Text(new_text))) => {
-                trace!("update text: {:?} -> {:?}", old_text, new_text);
-                Self::update_text(
-                    &Self::get_child(parent, index)?.dyn_into::<Text>()?,
-                    new_text,
-                )
+
+            dom_node = node.next_sibling();
+            parent.remove_child(&node)?;
+        }
+
+        Ok(None)
+    }
+
+    fn is_text(node: &Html<Message>) -> bool {
+        match node {
+            virtual_dom::Node::Lazy(lazy) => Self::is_text(lazy.node()),
+            virtual_dom::Node::Text(_) => true,
+            virtual_dom::Node::Element(_) => false,
+        }
+    }
+
+    // Adopts `dom_node` as the rendering of `new` if it matches, and otherwise
+    // puts a new node in its place, returning the DOM node that represents
+    // `new`.
+    fn hydrate_node(
+        &self,
+        new: &Html<Message>,
+        dom_node: Option<&Node>,
+        parent: &Node,
+    ) -> Result<Node, JsValue> {
+        match new {
+            virtual_dom::Node::Lazy(lazy) => self.hydrate_node(lazy.node(), dom_node, parent),
+            // empty text isn't in the markup at all
+            virtual_dom::Node::Text(text) if text.is_empty() => {
+                let node: Node = self.create_text(text).into();
+                parent.insert_before(&node, dom_node)?;
+
+                Ok(node)
             }
-            // update element
-            (Some(virtual_dom::Node::Element(old)), Some(virtual_dom::Node::Element(new)))
-                if old.name == new.name =>
-            {
-                trace!("update element: {:?}", new.name);
-                self.update_element(old, new, &Self::get_child(parent, index)?.dyn_into()?)?;
+            virtual_dom::Node::Text(text) => {
+                let dom_text = match dom_node.and_then(|node| node.dyn_ref::<Text>()) {
+                    Some(dom_text) => dom_text,
+                    None => return self.hydrate_mismatch(new, dom_node, parent),
+                };
+
+                let data = dom_text.data();
+
+                if data.starts_with(text.as_str()) && data != *text {
+                    // adjacent text nodes are merged in the markup
+                    dom_text.split_text(text.encode_utf16().count() as u32)?;
+                } else if data != *text {
+                    warn!("hydration: replacing text {:?} with {:?}", data, text);
+                    dom_text.set_data(text);
+                }
+
+                Ok(dom_text.clone().into())
+            }
+            virtual_dom::Node::Element(element) => {
+                let dom_element = match dom_node.and_then(|node| node.dyn_ref::<web_sys::Element>())
+                {
+                    Some(dom_element)
+                        if dom_element.local_name().eq_ignore_ascii_case(&element.name) =>
+                    {
+                        dom_element
+                    }
+                    _ => return self.hydrate_mismatch(new, dom_node, parent),
+                };
+
+                self.hydrate_element(element, dom_element)?;
+
+                Ok(dom_element.clone().into())
+            }
+        }
+    }
+
+    fn hydrate_element(
+        &self,
+        element: &virtual_dom::Element<Message>,
+        dom_element: &web_sys::Element,
+    ) -> Result<(), JsValue> {
+        let (attributes, text) = html_string::markup(element);
+
+        for name in dom_element.get_attribute_names().iter() {
+            let name = name.as_string().unwrap_or_default();
+
+            if !attributes.iter().any(|(other, _)| *other == name) {
+                warn!(
+                    "hydration: removing attribute {:?} of {:?}",
+                    name, element.name
+                );
+                dom_element.remove_attribute(&name)?;
             }
-            // replace node
-            (Some(old), Some(new)) => {
-                trace!("replace node: {:?} -> {:?} {}", old, new, index);
-                Self::replace_child(parent, index, &self.create_node(new)?)?
+        }
+
+        for (name, value) in &attributes {
+            if dom_element.get_attribute(name).as_ref() != Some(value) {
+                warn!(
+                    "hydration: setting attribute {:?} of {:?} to {:?}",
+                    name, element.name, value
+                );
+                dom_element.set_attribute(name, value)?;
             }
         }
 
+        // the markup only has their initial values
+        for (name, value) in diff::properties(element) {
+            js_sys::Reflect::set(dom_element, &name.into(), &json::to_js_value(value)?)?;
+        }
+
+        self.delegation.set_handlers(element, dom_element)?;
+
+        // the element's text stands in for its children, like a textarea's value
+        if text.is_none() {
+            self.hydrate_children(&element.children.nodes(), dom_element)?;
+        }
+
         Ok(())
     }
 
-    fn get_child(element: &Element, index: u32) -> Result<Node, JsValue> {
-        element
-            .dyn_ref::<Node>()
-            .unwrap()
-            .child_nodes()
-            .item(index)
-            .ok_or_else(|| format!("no child at index {}", index).into())
+    fn hydrate_mismatch(
+        &self,
+        new: &Html<Message>,
+        dom_node: Option<&Node>,
+        parent: &Node,
+    ) -> Result<Node, JsValue> {
+        let node = self.create_node(new)?;
+
+        match dom_node {
+            Some(dom_node) => {
+                warn!(
+                    "hydration: replacing {:?} with {:?}",
+                    dom_node.node_name(),
+                    new
+                );
+                parent.replace_child(&node, dom_node)?;
+            }
+            None => {
+                warn!("hydration: inserting missing {:?}", new);
+                parent.append_child(&node)?;
+            }
+        }
+
+        Ok(node)
     }
 
-    fn remove_child(element: &Element, index: u32) -> Result<(), JsValue> {
-        let child = Self::get_child(element, index)?;
-        element.remove_child(&child)?;
+    fn apply(&self, patch: &Patch<Message>, dom_node: &Node) -> Result<(), JsValue> {
+        match patch {
+            Patch::Replace { path, node } => {
+                let old_node = Self::find(dom_node, path)?;
+                let parent = old_node
+                    .parent_node()
+                    .ok_or_else(|| JsValue::from("can't replace a node without a parent"))?;
 
-        Ok(())
-    }
+                parent.replace_child(&self.create_node(node)?, &old_node)?;
+                self.delegation.forget(&old_node);
+            }
+            Patch::Insert { path, index, node } => {
+                let parent = Self::find(dom_node, path)?;
+                let next_sibling = parent.child_nodes().item(*index as u32);
+
+                parent.insert_before(&self.create_node(node)?, next_sibling.as_ref())?;
+            }
+            Patch::Remove { path, index } => {
+                let parent = Self::find(dom_node, path)?;
+                let child = Self::get_child(&parent, *index as u32)?;
 
-    fn replace_child(element: &Element, index: u32, new: &Node) -> Result<(), JsValue> {
-        let old = Self::get_child(element, index)?;
-        element.replace_child(new, &old)?;
+                parent.remove_child(&child)?;
+                self.delegation.forget(&child);
+            }
+            Patch::Move { path, from, to } => {
+                let parent = Self::find(dom_node, path)?;
+                let child = Self::get_child(&parent, *from as u32)?;
+                // the sibling it ends up before, counted before it is taken out
+                let next_sibling = if to >= from { to + 1 } else { *to };
+                let next_sibling = parent.child_nodes().item(next_sibling as u32);
+
+                parent.insert_before(&child, next_sibling.as_ref())?;
+            }
+            Patch::SetText { path, text } => {
+                Self::find(dom_node, path)?.set_text_content(Some(text));
+            }
+            Patch::SetAttribute { path, name, value } => {
+                Self::find_element(dom_node, path)?.set_attribute(name, value)?;
+            }
+            Patch::RemoveAttribute { path, name } => {
+                Self::find_element(dom_node, path)?.remove_attribute(name)?;
+            }
+            Patch::SetProperty { path, name, value } => {
+                let element = Self::find_element(dom_node, path)?;
+                js_sys::Reflect::set(&element, &(*name).into(), &json::to_js_value(value)?)?;
+            }
+            Patch::RemoveProperty { path, name } => {
+                let element = Self::find_element(dom_node, path)?;
+                js_sys::Reflect::set(&element, &(*name).into(), &JsValue::NULL)?;
+            }
+            Patch::SetHandlers { path, element } => {
+                self.delegation
+                    .set_handlers(element, &Self::find_element(dom_node, path)?)?;
+            }
+        }
 
         Ok(())
     }
 
-    fn append_child(element: &Element, child: &Node) -> Result<(), JsValue> {
-        element.append_child(child)?;
+    // Follows `path` from `dom_node`.
+    fn find(dom_node: &Node, path: &[usize]) -> Result<Node, JsValue> {
+        path.iter().try_fold(dom_node.clone(), |node, index| {
+            Self::get_child(&node, *index as u32)
+        })
+    }
 
-        Ok(())
+    fn find_element(dom_node: &Node, path: &[usize]) -> Result<web_sys::Element, JsValue> {
+        Self::find(dom_node, path)?
+            .dyn_into()
+            .map_err(|node| format!("not an element: {:?}", node).into())
+    }
+
+    fn get_child(parent: &Node, index: u32) -> Result<Node, JsValue> {
+        parent
+            .child_nodes()
+            .item(index)
+            .ok_or_else(|| format!("no child at index {}", index).into())
     }
 
     fn create_node(&self, node: &Html<Message>) -> Result<Node, JsValue> {
         Ok(match node {
             virtual_dom::Node::Element(element) => self.create_element(element)?.dyn_into()?,
             virtual_dom::Node::Text(text) => self.create_text(text).dyn_into()?,
+            virtual_dom::Node::Lazy(lazy) => self.create_node(lazy.node())?,
         })
     }
 
@@ -137,122 +332,25 @@ where
             .create_element(&element.name)?
             .dyn_into::<HtmlElement>()?;
 
-        self.set_attributes(element, &dom_element)?;
-
-        for child in &element.children {
-            let dom_child = self.create_node(child)?;
-            dom_element.append_child(&dom_child)?;
+        for (name, value) in diff::attributes(element) {
+            dom_element.set_attribute(name, value)?;
         }
 
-        Ok(dom_element)
-    }
-
-    fn update_element(
-        &self,
-        old: &virtual_dom::Element<Message>,
-        new: &virtual_dom::Element<Message>,
-        dom_element: &HtmlElement,
-    ) -> Result<(), JsValue> {
-        self.clear_attributes(old, dom_element)?;
-        self.set_attributes(new, dom_element)?;
-
-        let max_children = old.children.len().max(new.children.len());
-
-        for index in (0..max_children).rev() {
-            let old_child = old.children.get(index);
-            let new_child = new.children.get(index);
-
-            self.render_node(&old_child, &new_child, dom_element, index as u32)?;
+        for (name, value) in diff::properties(element) {
+            js_sys::Reflect::set(&dom_element, &name.into(), &json::to_js_value(value)?)?;
         }
 
-        Ok(())
-    }
-
-    fn clear_attributes(
-        &self,
-        element: &virtual_dom::Element<Message>,
-        dom_element: &HtmlElement,
-    ) -> Result<(), JsValue> {
-        for attribute in &element.attributes {
-            match attribute {
-                virtual_dom::Attribute::On(event) => match event {
-                    virtual_dom::Event::Click(_) => dom_element.set_onclick(None),
-                    virtual_dom::Event::Input(_) => dom_element.set_oninput(None),
-                },
-                virtual_dom::Attribute::Bool(name, _) => dom_element.set_attribute(name, "")?,
-                virtual_dom::Attribute::Text(name, _) => dom_element.set_attribute(name, "")?,
-            }
-        }
-
-        Ok(())
-    }
+        self.delegation.set_handlers(element, &dom_element)?;
 
-    fn set_attributes(
-        &self,
-        element: &virtual_dom::Element<Message>,
-        dom_element: &HtmlElement,
-    ) -> Result<(), JsValue> {
-        for attribute in &element.attributes {
-            match attribute {
-                virtual_dom::Attribute::On(event) => {
-                    let app = self.app.clone();
-
-                    match event {
-                        virtual_dom::Event::Click(message) => {
-                            let message = message.clone();
-
-                            let callback = Closure::wrap(Box::new(move || {
-                                app.handle_message(message.clone());
-                            })
-                                as Box<dyn FnMut()>);
-
-                            dom_element.set_onclick(Some(callback.as_ref().unchecked_ref()));
-
-                            // TODO: this is leaking memory
-                            callback.forget();
-                        }
-                        virtual_dom::Event::Input(handler) => {
-                            let handler = handler.clone();
-
-                            let callback = Closure::wrap(Box::new(move |event: InputEvent| {
-                                let value = event
-                                    .target()
-                                    .unwrap()
-                                    .dyn_into::<web_sys::HtmlInputElement>()
-                                    .unwrap()
-                                    .value();
-                                let message = handler(&value);
-                                app.handle_message(message);
-                            })
-                                as Box<dyn Fn(_)>);
-
-                            dom_element.set_oninput(Some(callback.as_ref().unchecked_ref()));
-
-                            // TODO: this is leaking memory
-                            callback.forget();
-                        }
-                    }
-                }
-                virtual_dom::Attribute::Bool(name, value) => {
-                    // https://developer.mozilla.org/en-US/docs/Web/HTML/Attributes#boolean_attributes
-                    if *value {
-                        dom_element.set_attribute(name, "")?
-                    }
-                }
-                virtual_dom::Attribute::Text(name, value) => {
-                    dom_element.set_attribute(name, value)?
-                }
-            }
+        for child in element.children.nodes() {
+            let dom_child = self.create_node(child)?;
+            dom_element.append_child(&dom_child)?;
         }
 
-        Ok(())
+        Ok(dom_element)
     }
 
     fn create_text(&self, text: &str) -> Text {
         self.document.create_text_node(text)
     }
-
-    fn update_text(text_node: &Text, text: &str) {
-        text_node.set_text_content(Some(text));
-    }
 }
@@ -0,0 +1,54 @@
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{Element, Node, ShadowRoot};
+
+/// Where an app is mounted.
+#[derive(Clone, Debug)]
+pub enum Root {
+    /// The element with this id in the document, looked up when the app
+    /// renders.
+    Id(String),
+    Element(Element),
+    ShadowRoot(ShadowRoot),
+}
+
+impl Root {
+    /// The node the app renders into, or a descriptive error if there is no
+    /// element with the root's id.
+    pub fn node(&self) -> Result<Node, JsValue> {
+        match self {
+            Root::Id(id) => web_sys::window()
+                .ok_or_else(|| JsValue::from("no window"))?
+                .document()
+                .ok_or_else(|| JsValue::from("no document"))?
+                .get_element_by_id(id)
+                .map(JsCast::unchecked_into)
+                .ok_or_else(|| format!("no element with id {:?} to mount the app on", id).into()),
+            Root::Element(element) => Ok(element.clone().into()),
+            Root::ShadowRoot(shadow_root) => Ok(shadow_root.clone().into()),
+        }
+    }
+}
+
+impl From<&str> for Root {
+    fn from(id: &str) -> Self {
+        Root::Id(id.into())
+    }
+}
+
+impl From<String> for Root {
+    fn from(id: String) -> Self {
+        Root::Id(id)
+    }
+}
+
+impl From<Element> for Root {
+    fn from(element: Element) -> Self {
+        Root::Element(element)
+    }
+}
+
+impl From<ShadowRoot> for Root {
+    fn from(shadow_root: ShadowRoot) -> Self {
+        Root::ShadowRoot(shadow_root)
+    }
+}
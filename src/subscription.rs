@@ -0,0 +1,123 @@
+use std::rc::Rc;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::EventTarget;
+
+/// A source of messages that the app listens to for as long as `subscriptions`
+/// keeps returning it.
+///
+/// Listening and producing messages are split in two so that a listener can
+/// be kept alive across updates while the latest subscription (and therefore
+/// the latest handler) is used to turn its events into messages.
+pub trait Subscription<Message> {
+    /// Identifies the underlying listener. Subscriptions with the same id
+    /// returned from consecutive calls to `subscriptions` share one listener.
+    fn id(&self) -> String;
+
+    /// Starts listening, sending every event to `sink`. The listener is torn
+    /// down when the returned `Listener` is dropped.
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue>;
+
+    /// Turns an event sent to the sink into a message, or ignores it.
+    fn message(&self, value: JsValue) -> Option<Message>;
+
+    fn boxed(self) -> Box<Self>
+    where
+        Self: Sized,
+    {
+        Box::new(self)
+    }
+}
+
+pub fn map<Message, OtherMessage, F>(subscription: Boxed<Message>, f: F) -> Boxed<OtherMessage>
+where
+    Message: 'static,
+    OtherMessage: 'static,
+    F: 'static + Fn(Message) -> OtherMessage,
+{
+    Mapped {
+        subscription,
+        f: Box::new(f),
+    }
+    .boxed()
+}
+
+pub type Boxed<T> = Box<dyn Subscription<T>>;
+pub type Subs<T> = Vec<Boxed<T>>;
+
+struct Mapped<Message, MappedMessage> {
+    subscription: Boxed<Message>,
+    f: Box<dyn Fn(Message) -> MappedMessage>,
+}
+
+impl<Message, MappedMessage> Subscription<MappedMessage> for Mapped<Message, MappedMessage> {
+    fn id(&self) -> String {
+        self.subscription.id()
+    }
+
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue> {
+        self.subscription.listen(sink)
+    }
+
+    fn message(&self, value: JsValue) -> Option<MappedMessage> {
+        self.subscription.message(value).map(&self.f)
+    }
+}
+
+/// Receives the events of a listener on behalf of the app.
+#[derive(Clone)]
+pub struct Sink(Rc<dyn Fn(JsValue)>);
+
+impl Sink {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: 'static + Fn(JsValue),
+    {
+        Self(Rc::new(f))
+    }
+
+    pub fn send(&self, value: JsValue) {
+        (self.0)(value)
+    }
+}
+
+/// A running listener, which is torn down when dropped.
+pub struct Listener {
+    teardown: Option<Box<dyn FnOnce()>>,
+}
+
+impl Listener {
+    pub fn new<Teardown>(teardown: Teardown) -> Self
+    where
+        Teardown: 'static + FnOnce(),
+    {
+        Self {
+            teardown: Some(Box::new(teardown)),
+        }
+    }
+
+    /// Sends every `event_type` event dispatched on `target` to `sink`.
+    pub fn event(target: &EventTarget, event_type: &str, sink: Sink) -> Result<Self, JsValue> {
+        let callback = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            sink.send(event.into());
+        }) as Box<dyn Fn(_)>);
+
+        target.add_event_listener_with_callback(event_type, callback.as_ref().unchecked_ref())?;
+
+        let target = target.clone();
+        let event_type = event_type.to_string();
+
+        Ok(Self::new(move || {
+            target
+                .remove_event_listener_with_callback(&event_type, callback.as_ref().unchecked_ref())
+                .unwrap();
+        }))
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(teardown) = self.teardown.take() {
+            teardown();
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! An opt-in layer over the element and attribute constructors that rejects,
+//! at compile time, attributes an element doesn't have. Global attributes
+//! and event handlers go on any element; the others only on the elements
+//! that define them, according to the tables in the `macros` crate.
+//!
+//! Typed elements are `Html` like any other, so the typed and untyped
+//! constructors can be mixed in a view.
+//!
+//! ```
+//! use rust_elm_architecture::{
+//!     typed::{self, attribute},
+//!     Html,
+//! };
+//!
+//! let link: Html<()> = typed::a(
+//!     vec![attribute::class("nav"), attribute::href("/about")],
+//!     vec!["About".into()],
+//! );
+//! ```
+//!
+//! ```compile_fail
+//! use rust_elm_architecture::{
+//!     typed::{self, attribute},
+//!     Html,
+//! };
+//!
+//! let view: Html<()> = typed::div(vec![attribute::href("/about")], vec![]);
+//! ```
+
+use crate::virtual_dom::{Attribute, Html, Options};
+use macros::typed_elements;
+use std::marker::PhantomData;
+
+/// An attribute of an element of type `Element`, from `element`.
+pub struct TypedAttribute<Element, Message> {
+    attribute: Attribute<Message>,
+    element: PhantomData<Element>,
+}
+
+impl<Element, Message> TypedAttribute<Element, Message> {
+    /// Any attribute, without checking that the element has it, for what the
+    /// tables don't cover, such as `data-*` attributes.
+    pub fn unchecked(attribute: Attribute<Message>) -> Self {
+        Self {
+            attribute,
+            element: PhantomData,
+        }
+    }
+
+    /// Sets how the listener is added to the DOM, if this is one.
+    pub fn with_options(self, options: Options) -> Self {
+        Self::unchecked(self.attribute.with_options(options))
+    }
+}
+
+impl<Element, Message> From<TypedAttribute<Element, Message>> for Attribute<Message> {
+    fn from(typed: TypedAttribute<Element, Message>) -> Self {
+        typed.attribute
+    }
+}
+
+typed_elements!();
+
+/// The typed attribute constructors, which take what the `Attribute`
+/// constructors of the same name do.
+pub mod attribute {
+    use super::{has, TypedAttribute};
+    use crate::{
+        decode::Decoder,
+        virtual_dom::{Attribute, Handled},
+    };
+    use macros::typed_attributes;
+
+    pub fn on<Element, Message>(
+        name: &str,
+        decoder: Decoder<Message>,
+    ) -> TypedAttribute<Element, Message>
+    where
+        Element: has::Global,
+        Message: 'static,
+    {
+        TypedAttribute::unchecked(Attribute::on(name, decoder))
+    }
+
+    pub fn custom<Element, Message>(
+        name: &str,
+        decoder: Decoder<Handled<Message>>,
+    ) -> TypedAttribute<Element, Message>
+    where
+        Element: has::Global,
+    {
+        TypedAttribute::unchecked(Attribute::custom(name, decoder))
+    }
+
+    pub fn on_click<Element, Message>(message: Message) -> TypedAttribute<Element, Message>
+    where
+        Element: has::Global,
+        Message: 'static + Clone,
+    {
+        TypedAttribute::unchecked(Attribute::on_click(message))
+    }
+
+    pub fn on_submit<Element, Message>(message: Message) -> TypedAttribute<Element, Message>
+    where
+        Element: has::Global,
+        Message: 'static + Clone,
+    {
+        TypedAttribute::unchecked(Attribute::on_submit(message))
+    }
+
+    pub fn on_input<Element, Message, F>(handler: F) -> TypedAttribute<Element, Message>
+    where
+        Element: has::Global,
+        Message: 'static,
+        F: 'static + Fn(&str) -> Message,
+    {
+        TypedAttribute::unchecked(Attribute::on_input(handler))
+    }
+
+    typed_attributes!();
+}
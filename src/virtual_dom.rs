@@ -1,20 +1,93 @@
-use macros::{attribute, bool_attribute, element};
-use std::{fmt, rc::Rc};
+pub(crate) mod diff;
+pub(crate) mod html_string;
 
-pub enum Event<Message> {
-    Click(Message),
-    Input(Rc<dyn Fn(&str) -> Message>),
+pub use diff::{diff, Patch, Path};
+
+use crate::decode::{self, Decoder};
+use macros::{attributes, elements};
+use std::{
+    any::{Any, TypeId},
+    cell::OnceCell,
+    fmt, mem,
+    rc::Rc,
+};
+
+/// A listener for the DOM event `name`, which decodes the event object into
+/// what to do with it. Events the decoder fails on are ignored.
+pub struct Event<Message> {
+    pub name: String,
+    pub decoder: Decoder<Handled<Message>>,
+    pub options: Options,
 }
 
 impl<Message> Event<Message> {
     fn map<OtherMessage, F>(self, f: F) -> Event<OtherMessage>
     where
         Message: 'static,
+        OtherMessage: 'static,
         F: 'static + Copy + Fn(Message) -> OtherMessage,
     {
-        match self {
-            Event::Click(message) => Event::Click(f(message)),
-            Event::Input(handler) => Event::Input(Rc::new(move |input| f(handler(input)))),
+        Event {
+            name: self.name,
+            decoder: self.decoder.map(move |handled| handled.map(f)),
+            options: self.options,
+        }
+    }
+}
+
+/// How a listener is added to the DOM, see
+/// https://developer.mozilla.org/en-US/docs/Web/API/EventTarget/addEventListener#options
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Options {
+    pub passive: bool,
+    pub capture: bool,
+    pub once: bool,
+}
+
+/// What to do with a decoded event: the message to send, if any, and whether
+/// to stop it propagating or prevent the browser's default behavior.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Handled<Message> {
+    pub message: Option<Message>,
+    pub stop_propagation: bool,
+    pub prevent_default: bool,
+}
+
+impl<Message> Handled<Message> {
+    pub fn message(message: Message) -> Self {
+        Self {
+            message: Some(message),
+            stop_propagation: false,
+            prevent_default: false,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            message: None,
+            stop_propagation: false,
+            prevent_default: false,
+        }
+    }
+
+    pub fn stop_propagation(mut self) -> Self {
+        self.stop_propagation = true;
+        self
+    }
+
+    pub fn prevent_default(mut self) -> Self {
+        self.prevent_default = true;
+        self
+    }
+
+    fn map<OtherMessage, F>(self, f: F) -> Handled<OtherMessage>
+    where
+        F: Fn(Message) -> OtherMessage,
+    {
+        Handled {
+            message: self.message.map(f),
+            stop_propagation: self.stop_propagation,
+            prevent_default: self.prevent_default,
         }
     }
 }
@@ -23,170 +96,163 @@ pub enum Attribute<Message> {
     On(Event<Message>),
     Bool(String, bool),
     Text(String, String),
+    /// A JS property of the element rather than an HTML attribute. Its value
+    /// is kept as JSON so that views can be built and compared outside the
+    /// browser.
+    Property(String, serde_json::Value),
 }
 
 impl<Message> Attribute<Message> {
-    pub fn on_click(message: Message) -> Self {
-        Self::On(Event::Click(message))
+    /// Sets the JS property `name` of the element, which unlike an attribute
+    /// reflects the live state of form controls.
+    pub fn property<Value>(name: &str, value: Value) -> Self
+    where
+        Value: Into<serde_json::Value>,
+    {
+        Self::Property(name.into(), value.into())
+    }
+
+    pub fn value(value: &str) -> Self {
+        Self::property("value", value)
+    }
+
+    pub fn checked(value: bool) -> Self {
+        Self::property("checked", value)
+    }
+
+    pub fn selected(value: bool) -> Self {
+        Self::property("selected", value)
     }
 
-    pub fn on_input<F: 'static + Fn(&str) -> Message>(handler: F) -> Self {
-        Self::On(Event::Input(Rc::new(handler)))
+    /// Listens for the DOM event `name`, sending the message `decoder`
+    /// decodes from the event object.
+    pub fn on(name: &str, decoder: Decoder<Message>) -> Self
+    where
+        Message: 'static,
+    {
+        Self::custom(name, decoder.map(Handled::message))
+    }
+
+    /// Like `on`, but the decoder also decides whether to prevent the
+    /// browser's default behavior.
+    pub fn prevent_default_on(name: &str, decoder: Decoder<(Message, bool)>) -> Self
+    where
+        Message: 'static,
+    {
+        Self::custom(
+            name,
+            decoder.map(|(message, prevent_default)| Handled {
+                prevent_default,
+                ..Handled::message(message)
+            }),
+        )
+    }
+
+    /// Like `on`, but the decoder also decides whether to stop the event
+    /// propagating.
+    pub fn stop_propagation_on(name: &str, decoder: Decoder<(Message, bool)>) -> Self
+    where
+        Message: 'static,
+    {
+        Self::custom(
+            name,
+            decoder.map(|(message, stop_propagation)| Handled {
+                stop_propagation,
+                ..Handled::message(message)
+            }),
+        )
+    }
+
+    /// Listens for the DOM event `name`, letting the decoder decide what to
+    /// do with each event.
+    pub fn custom(name: &str, decoder: Decoder<Handled<Message>>) -> Self {
+        Self::On(Event {
+            name: name.into(),
+            decoder,
+            options: Options::default(),
+        })
+    }
+
+    /// Sets how the listener is added to the DOM, if this is one.
+    pub fn with_options(self, options: Options) -> Self {
+        match self {
+            Self::On(event) => Self::On(Event { options, ..event }),
+            attribute => attribute,
+        }
+    }
+
+    pub fn on_click(message: Message) -> Self
+    where
+        Message: 'static + Clone,
+    {
+        Self::on("click", decode::succeed(message))
+    }
+
+    /// Listens for form submissions, preventing the page from reloading.
+    pub fn on_submit(message: Message) -> Self
+    where
+        Message: 'static + Clone,
+    {
+        Self::custom(
+            "submit",
+            decode::succeed(Handled::message(message).prevent_default()),
+        )
+    }
+
+    /// Listens for input events, passing the value of the event's target to
+    /// `handler`.
+    pub fn on_input<F>(handler: F) -> Self
+    where
+        Message: 'static,
+        F: 'static + Fn(&str) -> Message,
+    {
+        Self::on(
+            "input",
+            decode::target_value().map(move |value| handler(&value)),
+        )
     }
 
     fn map<OtherMessage, F>(self, f: F) -> Attribute<OtherMessage>
     where
         Message: 'static,
+        OtherMessage: 'static,
         F: 'static + Copy + Fn(Message) -> OtherMessage,
     {
         match self {
             Attribute::On(event) => Attribute::On(event.map(f)),
             Attribute::Bool(name, value) => Attribute::Bool(name, value),
             Attribute::Text(name, value) => Attribute::Text(name, value),
+            Attribute::Property(name, value) => Attribute::Property(name, value),
         }
     }
 
-    // From https://developer.mozilla.org/en-US/docs/Web/HTML/Attributes
-
-    attribute!("accept");
-    attribute!("accept-charset");
-    attribute!("accesskey");
-    attribute!("action");
-    attribute!("align");
-    attribute!("allow");
-    attribute!("alt");
-    attribute!("async", "async_");
-    attribute!("autocapitalize");
-    attribute!("autocomplete");
-    attribute!("autofocus");
-    attribute!("autoplay");
-    attribute!("buffered");
-    attribute!("capture");
-    attribute!("challenge");
-    attribute!("charset");
-    attribute!("checked");
-    attribute!("cite");
-    attribute!("class");
-    attribute!("code");
-    attribute!("codebase");
-    attribute!("color");
-    attribute!("cols");
-    attribute!("colspan");
-    attribute!("content");
-    attribute!("contenteditable");
-    attribute!("contextmenu");
-    attribute!("controls");
-    attribute!("coords");
-    attribute!("crossorigin");
-    attribute!("csp");
-    attribute!("data");
-    attribute!("datetime");
-    attribute!("decoding");
-    attribute!("default");
-    attribute!("defer");
-    attribute!("dir");
-    attribute!("dirname");
-    bool_attribute!("disabled");
-    attribute!("download");
-    attribute!("draggable");
-    attribute!("enctype");
-    attribute!("enterkeyhint");
-    attribute!("for", "for_");
-    attribute!("form");
-    attribute!("formaction");
-    attribute!("formenctype");
-    attribute!("formmethod");
-    bool_attribute!("formnovalidate");
-    attribute!("formtarget");
-    attribute!("headers");
-    attribute!("height");
-    attribute!("hidden");
-    attribute!("high");
-    attribute!("href");
-    attribute!("hreflang");
-    attribute!("icon");
-    attribute!("id");
-    attribute!("importance");
-    attribute!("integrity");
-    attribute!("intrinsicsize");
-    attribute!("inputmode");
-    attribute!("ismap");
-    attribute!("itemprop");
-    attribute!("keytype");
-    attribute!("kind");
-    attribute!("label");
-    attribute!("lang");
-    attribute!("language");
-    attribute!("loading");
-    attribute!("list");
-    attribute!("loop", "loop_");
-    attribute!("low");
-    attribute!("manifest");
-    attribute!("map", "map_");
-    attribute!("max");
-    attribute!("maxlength");
-    attribute!("minlength");
-    attribute!("media");
-    attribute!("method");
-    attribute!("min");
-    attribute!("multiple");
-    attribute!("muted");
-    attribute!("name");
-    attribute!("novalidate");
-    attribute!("open");
-    attribute!("optimum");
-    attribute!("pattern");
-    attribute!("ping");
-    attribute!("placeholder");
-    attribute!("poster");
-    attribute!("preload");
-    attribute!("radiogroup");
-    bool_attribute!("readonly");
-    attribute!("referrerpolicy");
-    attribute!("rel");
-    bool_attribute!("required");
-    attribute!("reversed");
-    attribute!("rows");
-    attribute!("rowspan");
-    attribute!("sandbox");
-    attribute!("scope");
-    attribute!("scoped");
-    attribute!("selected");
-    attribute!("shape");
-    attribute!("size");
-    attribute!("sizes");
-    attribute!("slot");
-    attribute!("span");
-    attribute!("spellcheck");
-    attribute!("src");
-    attribute!("srcdoc");
-    attribute!("srclang");
-    attribute!("srcset");
-    attribute!("start");
-    attribute!("step");
-    attribute!("style");
-    attribute!("summary");
-    attribute!("tabindex");
-    attribute!("target");
-    attribute!("title");
-    attribute!("translate");
-    attribute!("type", "type_");
-    attribute!("usemap");
-    attribute!("value");
-    attribute!("width");
-    attribute!("wrap");
+    // a constructor for every attribute `html!` accepts, but the properties
+    attributes!();
 }
 
 pub struct Element<Message> {
     pub name: String,
     pub attributes: Vec<Attribute<Message>>,
-    pub children: Vec<Node<Message>>,
+    pub children: Children<Message>,
 }
 
 impl<Message> Element<Message> {
+    pub fn new(
+        name: &str,
+        attributes: Vec<Attribute<Message>>,
+        children: Children<Message>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            attributes,
+            children,
+        }
+    }
+
     fn map<OtherMessage, F>(self, f: F) -> Element<OtherMessage>
     where
         Message: 'static,
+        OtherMessage: 'static,
         F: 'static + Copy + Fn(Message) -> OtherMessage,
     {
         Element {
@@ -196,11 +262,54 @@ impl<Message> Element<Message> {
                 .into_iter()
                 .map(|attribute| attribute.map(f))
                 .collect(),
-            children: self
-                .children
-                .into_iter()
-                .map(|child| child.map(f))
-                .collect(),
+            children: self.children.map(f),
+        }
+    }
+}
+
+/// An element's children. Keyed children are matched up by key instead of
+/// position when they are rendered, so that they can be moved, inserted and
+/// removed without touching their siblings.
+pub enum Children<Message> {
+    Unkeyed(Vec<Node<Message>>),
+    Keyed(Vec<(String, Node<Message>)>),
+}
+
+impl<Message> Children<Message> {
+    pub fn len(&self) -> usize {
+        match self {
+            Children::Unkeyed(children) => children.len(),
+            Children::Keyed(children) => children.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn nodes(&self) -> Vec<&Node<Message>> {
+        match self {
+            Children::Unkeyed(children) => children.iter().collect(),
+            Children::Keyed(children) => children.iter().map(|(_, child)| child).collect(),
+        }
+    }
+
+    fn map<OtherMessage, F>(self, f: F) -> Children<OtherMessage>
+    where
+        Message: 'static,
+        OtherMessage: 'static,
+        F: 'static + Copy + Fn(Message) -> OtherMessage,
+    {
+        match self {
+            Children::Unkeyed(children) => {
+                Children::Unkeyed(children.into_iter().map(|child| child.map(f)).collect())
+            }
+            Children::Keyed(children) => Children::Keyed(
+                children
+                    .into_iter()
+                    .map(|(key, child)| (key, child.map(f)))
+                    .collect(),
+            ),
         }
     }
 }
@@ -211,10 +320,110 @@ impl<Message> fmt::Debug for Element<Message> {
     }
 }
 
+// The arguments of a lazy node, compared to decide whether its view has to be
+// called again.
+trait Arguments {
+    fn as_any(&self) -> &dyn Any;
+    fn equals(&self, other: &dyn Arguments) -> bool;
+}
+
+impl<T> Arguments for T
+where
+    T: 'static + PartialEq,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn equals(&self, other: &dyn Arguments) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<T>()
+            .is_some_and(|other| self == other)
+    }
+}
+
+#[derive(Clone)]
+struct LazyKey {
+    view: usize,
+    arguments: Rc<dyn Arguments>,
+    // the type of each function the node was mapped with, or `None` if it
+    // captured values and so can't be compared
+    mappers: Vec<Option<TypeId>>,
+}
+
+impl PartialEq for LazyKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.view == other.view
+            && self.arguments.equals(other.arguments.as_ref())
+            && self.mappers.len() == other.mappers.len()
+            && self
+                .mappers
+                .iter()
+                .zip(&other.mappers)
+                .all(|(mapper, other)| mapper.is_some() && mapper == other)
+    }
+}
+
+/// A node whose view is only called when it is rendered, and skipped along
+/// with diffing when the node it replaces had the same view and equal
+/// arguments.
+pub struct Lazy<Message> {
+    key: LazyKey,
+    view: Rc<dyn Fn() -> Node<Message>>,
+    node: OnceCell<Rc<Node<Message>>>,
+}
+
+impl<Message> Lazy<Message> {
+    /// The node returned by the view, which is only called the first time.
+    pub fn node(&self) -> &Node<Message> {
+        self.node.get_or_init(|| Rc::new((self.view)()))
+    }
+
+    /// Takes the already rendered node of `old` if it was built by the same
+    /// view from equal arguments, in which case nothing needs to be rendered.
+    pub fn reuse(&self, old: &Lazy<Message>) -> bool {
+        if self.key != old.key {
+            return false;
+        }
+
+        match old.node.get() {
+            Some(node) => self.node.set(node.clone()).is_ok(),
+            None => false,
+        }
+    }
+
+    fn map<OtherMessage, F>(self, f: F) -> Lazy<OtherMessage>
+    where
+        Message: 'static,
+        OtherMessage: 'static,
+        F: 'static + Copy + Fn(Message) -> OtherMessage,
+    {
+        let mut key = self.key;
+        key.mappers
+            .push((mem::size_of::<F>() == 0).then(TypeId::of::<F>));
+
+        let view = self.view;
+
+        Lazy {
+            key,
+            view: Rc::new(move || view().map(f)),
+            node: OnceCell::new(),
+        }
+    }
+}
+
+impl<Message> fmt::Debug for Lazy<Message> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("lazy")
+    }
+}
+
 #[derive(Debug)]
 pub enum Node<Message> {
     Element(Element<Message>),
     Text(String),
+    Lazy(Lazy<Message>),
 }
 
 impl<Message> Node<Message> {
@@ -222,148 +431,102 @@ impl<Message> Node<Message> {
         Node::Text(text.into())
     }
 
+    /// An element whose children are identified by a key, unique among its
+    /// children, rather than by their position.
+    pub fn keyed(
+        name: &str,
+        attributes: Vec<Attribute<Message>>,
+        children: Vec<(String, Node<Message>)>,
+    ) -> Node<Message> {
+        Node::Element(Element::new(name, attributes, Children::Keyed(children)))
+    }
+
     pub fn map<OtherMessage, F>(self, f: F) -> Node<OtherMessage>
     where
         Message: 'static,
+        OtherMessage: 'static,
         F: 'static + Copy + Fn(Message) -> OtherMessage,
     {
         match self {
             Node::Element(element) => Node::Element(element.map(f)),
             Node::Text(text) => Node::Text(text),
+            Node::Lazy(lazy) => Node::Lazy(lazy.map(f)),
         }
     }
 
-    // From https://developer.mozilla.org/en-US/docs/Web/HTML/Element
-
-    // Content Sectioning
-    element!("address");
-    element!("article");
-    element!("aside");
-    element!("footer");
-    element!("header");
-    element!("h1");
-    element!("h2");
-    element!("h3");
-    element!("h4");
-    element!("h5");
-    element!("h6");
-    element!("main");
-    element!("nav");
-    element!("section");
-
-    // Text Content
-    element!("blockquote");
-    element!("dd");
-    element!("div");
-    element!("d1");
-    element!("dt");
-    element!("figcaption");
-    element!("figure");
-    element!("hr");
-    element!("li");
-    element!("ol");
-    element!("p");
-    element!("pre");
-    element!("ul");
-
-    // Inline Text Semantics
-    element!("a");
-    element!("abbr");
-    element!("b");
-    element!("bdi");
-    element!("bdo");
-    element!("br");
-    element!("cite");
-    element!("code");
-    element!("data");
-    element!("dfn");
-    element!("em");
-    element!("i");
-    element!("kbd");
-    element!("mark");
-    element!("q");
-    element!("rp");
-    element!("rt");
-    element!("ruby");
-    element!("s");
-    element!("samp");
-    element!("small");
-    element!("span");
-    element!("strong");
-    element!("sub");
-    element!("sup");
-    element!("time");
-    element!("u");
-    element!("var");
-    element!("wbr");
-
-    // Image and Multimedia
-    element!("area");
-    element!("audio");
-    element!("img");
-    // element!("map");
-    element!("track");
-    element!("video");
-
-    // Embedded Content
-    element!("embed");
-    element!("iframe");
-    element!("object");
-    element!("param");
-    element!("picture");
-    element!("portal");
-    element!("source");
-
-    // SVG and MathML
-    element!("svg");
-    element!("math");
-
-    // Scripting
-    element!("canvas");
-    element!("noscript");
-    element!("script");
-
-    // Demarcating Edits
-    element!("del");
-    element!("ins");
-
-    // Table Content
-    element!("caption");
-    element!("col");
-    element!("colgroup");
-    element!("table");
-    element!("tbody");
-    element!("td");
-    element!("tfoot");
-    element!("th");
-    element!("thead");
-    element!("tr");
-
-    // Forms
-    element!("button");
-    element!("datalist");
-    element!("fieldset");
-    element!("form");
-    element!("input");
-    element!("label");
-    element!("legend");
-    element!("meter");
-    element!("optgroup");
-    element!("option");
-    element!("output");
-    element!("progress");
-    element!("select");
-    element!("textarea");
-
-    // Interactive Elements
-    element!("details");
-    element!("dialog");
-    element!("menu");
-    element!("summary");
-
-    // Web Components
-    element!("slot");
-    element!("template");
+    /// A node built by `view` from `argument` when it is rendered. Rendering
+    /// is skipped entirely while `view` and `argument` stay the same.
+    pub fn lazy<A>(view: fn(&A) -> Node<Message>, argument: A) -> Node<Message>
+    where
+        Message: 'static,
+        A: 'static + PartialEq,
+    {
+        let argument = Rc::new(argument);
+
+        Self::lazy_with_key(view as usize, argument.clone(), move || view(&argument))
+    }
+
+    pub fn lazy2<A, B>(view: fn(&A, &B) -> Node<Message>, a: A, b: B) -> Node<Message>
+    where
+        Message: 'static,
+        A: 'static + PartialEq,
+        B: 'static + PartialEq,
+    {
+        let arguments = Rc::new((a, b));
+
+        Self::lazy_with_key(view as usize, arguments.clone(), move || {
+            view(&arguments.0, &arguments.1)
+        })
+    }
+
+    pub fn lazy3<A, B, C>(view: fn(&A, &B, &C) -> Node<Message>, a: A, b: B, c: C) -> Node<Message>
+    where
+        Message: 'static,
+        A: 'static + PartialEq,
+        B: 'static + PartialEq,
+        C: 'static + PartialEq,
+    {
+        let arguments = Rc::new((a, b, c));
+
+        Self::lazy_with_key(view as usize, arguments.clone(), move || {
+            view(&arguments.0, &arguments.1, &arguments.2)
+        })
+    }
+
+    fn lazy_with_key<V>(view: usize, arguments: Rc<dyn Arguments>, f: V) -> Node<Message>
+    where
+        V: 'static + Fn() -> Node<Message>,
+    {
+        Node::Lazy(Lazy {
+            key: LazyKey {
+                view,
+                arguments,
+                mappers: vec![],
+            },
+            view: Rc::new(f),
+            node: OnceCell::new(),
+        })
+    }
+
+    // a constructor for every element `html!` accepts
+    elements!();
+
+    #[deprecated(note = "the element is `dl`, use `Node::dl`")]
+    pub fn d1(attributes: Vec<Attribute<Message>>, children: Vec<Node<Message>>) -> Node<Message> {
+        Self::dl(attributes, children)
+    }
+}
+
+impl<Message> From<&str> for Node<Message> {
+    fn from(text: &str) -> Self {
+        Node::text(text)
+    }
+}
+
+impl<Message> From<String> for Node<Message> {
+    fn from(text: String) -> Self {
+        Node::Text(text)
+    }
 }
 
 pub type Html<Message> = Node<Message>;
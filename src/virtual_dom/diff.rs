@@ -0,0 +1,470 @@
+use super::{Attribute, Children, Element, Node};
+use std::collections::{HashMap, HashSet};
+
+/// The child indices leading from a node to one of its descendants, as they
+/// are when the patch containing it is applied.
+pub type Path = Vec<usize>;
+
+/// A change that turns the DOM rendered from one view into the DOM of the
+/// next. Patches are applied in order, and each path is relative to the DOM
+/// node of the old view's root.
+#[derive(Debug)]
+pub enum Patch<'a, Message> {
+    /// Replaces the node at `path` with one created from `node`.
+    Replace {
+        path: Path,
+        node: &'a Node<Message>,
+    },
+    /// Inserts a node created from `node` as child `index` of the element at
+    /// `path`, which appends it if `index` is the number of children.
+    Insert {
+        path: Path,
+        index: usize,
+        node: &'a Node<Message>,
+    },
+    /// Removes child `index` of the element at `path`.
+    Remove {
+        path: Path,
+        index: usize,
+    },
+    /// Moves child `from` of the element at `path` to index `to` among the
+    /// others.
+    Move {
+        path: Path,
+        from: usize,
+        to: usize,
+    },
+    SetText {
+        path: Path,
+        text: &'a str,
+    },
+    SetAttribute {
+        path: Path,
+        name: &'a str,
+        value: &'a str,
+    },
+    RemoveAttribute {
+        path: Path,
+        name: &'a str,
+    },
+    SetProperty {
+        path: Path,
+        name: &'a str,
+        value: &'a serde_json::Value,
+    },
+    /// Resets the property `name`, which the new view no longer sets, to
+    /// `null`.
+    RemoveProperty {
+        path: Path,
+        name: &'a str,
+    },
+    /// Replaces the event handlers of the element at `path` with the ones of
+    /// `element`.
+    SetHandlers {
+        path: Path,
+        element: &'a Element<Message>,
+    },
+}
+
+/// Returns the patches that turn the DOM rendered from `old` into the DOM of
+/// `new`. Lazy nodes in `new` that can reuse their counterpart in `old` are
+/// skipped, and the others are forced.
+pub fn diff<'a, Message>(
+    old: &'a Node<Message>,
+    new: &'a Node<Message>,
+) -> Vec<Patch<'a, Message>> {
+    let mut patches = vec![];
+    diff_node(old, new, &[], &mut patches);
+    patches
+}
+
+fn diff_node<'a, Message>(
+    old: &'a Node<Message>,
+    new: &'a Node<Message>,
+    path: &[usize],
+    patches: &mut Vec<Patch<'a, Message>>,
+) {
+    match (old, new) {
+        // skip lazy node
+        (Node::Lazy(old), Node::Lazy(new)) if new.reuse(old) => {}
+        // diff what lazy nodes render to
+        (Node::Lazy(old), new) => diff_node(old.node(), new, path, patches),
+        (old, Node::Lazy(new)) => diff_node(old, new.node(), path, patches),
+        // leave text unchanged
+        (Node::Text(old_text), Node::Text(new_text)) if old_text == new_text => {}
+        // update text
+        (Node::Text(_), Node::Text(new_text)) => patches.push(Patch::SetText {
+            path: path.to_vec(),
+            text: new_text,
+        }),
+        // update element
+        (Node::Element(old), Node::Element(new)) if old.name == new.name => {
+            diff_element(old, new, path, patches)
+        }
+        // replace node
+        (_, new) => patches.push(Patch::Replace {
+            path: path.to_vec(),
+            node: new,
+        }),
+    }
+}
+
+fn diff_element<'a, Message>(
+    old: &'a Element<Message>,
+    new: &'a Element<Message>,
+    path: &[usize],
+    patches: &mut Vec<Patch<'a, Message>>,
+) {
+    let old_attributes = attributes(old);
+    let new_attributes = attributes(new);
+
+    for name in old_attributes.keys() {
+        if !new_attributes.contains_key(name) {
+            patches.push(Patch::RemoveAttribute {
+                path: path.to_vec(),
+                name,
+            });
+        }
+    }
+
+    for (name, value) in &new_attributes {
+        if old_attributes.get(name) != Some(value) {
+            patches.push(Patch::SetAttribute {
+                path: path.to_vec(),
+                name,
+                value,
+            });
+        }
+    }
+
+    let old_properties = properties(old);
+    let new_properties = properties(new);
+
+    for name in old_properties.keys() {
+        if !new_properties.contains_key(name) {
+            patches.push(Patch::RemoveProperty {
+                path: path.to_vec(),
+                name,
+            });
+        }
+    }
+
+    for (name, value) in &new_properties {
+        if old_properties.get(name) != Some(value) {
+            patches.push(Patch::SetProperty {
+                path: path.to_vec(),
+                name,
+                value,
+            });
+        }
+    }
+
+    // decoders can't be compared, so handlers are replaced whenever there are
+    // any
+    if has_handlers(old) || has_handlers(new) {
+        patches.push(Patch::SetHandlers {
+            path: path.to_vec(),
+            element: new,
+        });
+    }
+
+    match (&old.children, &new.children) {
+        (Children::Unkeyed(old_children), Children::Unkeyed(new_children)) => {
+            diff_unkeyed_children(old_children, new_children, path, patches)
+        }
+        (Children::Keyed(old_children), Children::Keyed(new_children)) => {
+            diff_keyed_children(old_children, new_children, path, patches)
+        }
+        // replace children
+        (old_children, new_children) => {
+            for index in (0..old_children.len()).rev() {
+                patches.push(Patch::Remove {
+                    path: path.to_vec(),
+                    index,
+                });
+            }
+
+            for (index, node) in new_children.nodes().into_iter().enumerate() {
+                patches.push(Patch::Insert {
+                    path: path.to_vec(),
+                    index,
+                    node,
+                });
+            }
+        }
+    }
+}
+
+fn diff_unkeyed_children<'a, Message>(
+    old: &'a [Node<Message>],
+    new: &'a [Node<Message>],
+    path: &[usize],
+    patches: &mut Vec<Patch<'a, Message>>,
+) {
+    for (index, (old_child, new_child)) in old.iter().zip(new).enumerate() {
+        diff_node(old_child, new_child, &child_path(path, index), patches);
+    }
+
+    // remove from the end, so that the indices of the others stay the same
+    for index in (new.len()..old.len()).rev() {
+        patches.push(Patch::Remove {
+            path: path.to_vec(),
+            index,
+        });
+    }
+
+    for (index, node) in new.iter().enumerate().skip(old.len()) {
+        patches.push(Patch::Insert {
+            path: path.to_vec(),
+            index,
+            node,
+        });
+    }
+}
+
+// Where a keyed child is in the DOM while the patches are worked out.
+#[derive(Clone, Copy)]
+enum Place {
+    // at the old child's position, relative to the other old children
+    Old(usize),
+    // moved or inserted in front of the old child with this index, or of
+    // `old.len()` for the end, along with the others moved in front of it
+    Before(usize),
+}
+
+fn diff_keyed_children<'a, Message>(
+    old: &'a [(String, Node<Message>)],
+    new: &'a [(String, Node<Message>)],
+    path: &[usize],
+    patches: &mut Vec<Patch<'a, Message>>,
+) {
+    let mut old_indices = HashMap::new();
+    for (index, (key, _)) in old.iter().enumerate() {
+        old_indices.entry(key.as_str()).or_insert(index);
+    }
+
+    // the old child each new child takes the place of, if any; a key used
+    // twice only matches once
+    let matches: Vec<Option<usize>> = new
+        .iter()
+        .map(|(key, _)| old_indices.remove(key.as_str()))
+        .collect();
+
+    let mut matched = vec![false; old.len()];
+    for old_index in matches.iter().flatten() {
+        matched[*old_index] = true;
+    }
+
+    for index in (0..old.len()).rev() {
+        if !matched[index] {
+            patches.push(Patch::Remove {
+                path: path.to_vec(),
+                index,
+            });
+        }
+    }
+
+    // the old children still in their place, and the number of children moved
+    // in front of each old child, from which the DOM index of any child can
+    // be counted while the patches are applied
+    let mut in_place = Counts::new(old.len());
+    let mut moved_before = Counts::new(old.len() + 1);
+
+    for (index, matched) in matched.iter().enumerate() {
+        if *matched {
+            in_place.add(index, 1);
+        }
+    }
+
+    // the DOM index of a child, counting the old children in place before it
+    // and the children moved in front of them; a child moved in front of an
+    // old child is the first of those, since it goes right before its next
+    // sibling
+    let position = |in_place: &Counts, moved_before: &Counts, place| match place {
+        Place::Old(index) => in_place.before(index) + moved_before.before(index + 1),
+        Place::Before(index) => in_place.before(index) + moved_before.before(index),
+    };
+
+    // the largest set of children that are already in the right order,
+    // which can stay where they are while everything else moves around them
+    let stable = longest_increasing_subsequence(&matches);
+
+    // where each new child ended up, once it has been diffed
+    let mut places = vec![Place::Before(old.len()); new.len()];
+
+    // walk backwards, so that the next sibling is always in its final place
+    for (index, (_, new_child)) in new.iter().enumerate().rev() {
+        let next_place = places.get(index + 1).copied();
+        let next_sibling = match next_place {
+            Some(place) => position(&in_place, &moved_before, place),
+            None => in_place.before(old.len()) + moved_before.before(old.len() + 1),
+        };
+
+        // where the child goes if it has to be moved or inserted
+        let before = match next_place {
+            Some(Place::Old(index) | Place::Before(index)) => index,
+            None => old.len(),
+        };
+
+        match matches[index] {
+            Some(old_index) => {
+                let from = position(&in_place, &moved_before, Place::Old(old_index));
+                diff_node(
+                    &old[old_index].1,
+                    new_child,
+                    &child_path(path, from),
+                    patches,
+                );
+
+                let to = if from < next_sibling {
+                    next_sibling - 1
+                } else {
+                    next_sibling
+                };
+
+                places[index] = if !stable.contains(&index) && from != to {
+                    patches.push(Patch::Move {
+                        path: path.to_vec(),
+                        from,
+                        to,
+                    });
+                    in_place.add(old_index, -1);
+                    moved_before.add(before, 1);
+                    Place::Before(before)
+                } else {
+                    Place::Old(old_index)
+                };
+            }
+            None => {
+                patches.push(Patch::Insert {
+                    path: path.to_vec(),
+                    index: next_sibling,
+                    node: new_child,
+                });
+                moved_before.add(before, 1);
+                places[index] = Place::Before(before);
+            }
+        }
+    }
+}
+
+// Counts of children by index, which can be summed up to an index in
+// logarithmic time (a Fenwick tree).
+struct Counts(Vec<isize>);
+
+impl Counts {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len + 1])
+    }
+
+    fn add(&mut self, index: usize, count: isize) {
+        let mut node = index + 1;
+
+        while node < self.0.len() {
+            self.0[node] += count;
+            node += node & node.wrapping_neg();
+        }
+    }
+
+    // The sum of the counts of the indices before `index`.
+    fn before(&self, index: usize) -> usize {
+        let mut sum = 0;
+        let mut node = index;
+
+        while node > 0 {
+            sum += self.0[node];
+            node &= node - 1;
+        }
+
+        sum as usize
+    }
+}
+
+fn child_path(path: &[usize], index: usize) -> Path {
+    let mut path = path.to_vec();
+    path.push(index);
+    path
+}
+
+fn has_handlers<Message>(element: &Element<Message>) -> bool {
+    element
+        .attributes
+        .iter()
+        .any(|attribute| matches!(attribute, Attribute::On(_)))
+}
+
+/// Returns the HTML attributes of `element` by name, where a `false` boolean
+/// attribute is absent; a later one with the same name wins, as it would when
+/// setting them in order.
+pub(crate) fn attributes<Message>(element: &Element<Message>) -> HashMap<&str, &str> {
+    let mut attributes = HashMap::new();
+
+    for attribute in &element.attributes {
+        match attribute {
+            // https://developer.mozilla.org/en-US/docs/Web/HTML/Attributes#boolean_attributes
+            Attribute::Bool(name, true) => {
+                attributes.insert(name.as_str(), "");
+            }
+            Attribute::Bool(name, false) => {
+                attributes.remove(name.as_str());
+            }
+            Attribute::Text(name, value) => {
+                attributes.insert(name.as_str(), value.as_str());
+            }
+            Attribute::On(_) | Attribute::Property(..) => {}
+        }
+    }
+
+    attributes
+}
+
+/// Returns the properties of `element` by name; a later one with the same
+/// name wins, as it would when setting them in order.
+pub(crate) fn properties<Message>(element: &Element<Message>) -> HashMap<&str, &serde_json::Value> {
+    element
+        .attributes
+        .iter()
+        .filter_map(|attribute| match attribute {
+            Attribute::Property(name, value) => Some((name.as_str(), value)),
+            _ => None,
+        })
+        .collect()
+}
+
+// Returns the positions in `sequence` of a longest strictly increasing
+// subsequence of its values, skipping `None`s.
+fn longest_increasing_subsequence(sequence: &[Option<usize>]) -> HashSet<usize> {
+    // tails[length - 1] is the position of the smallest value ending an
+    // increasing subsequence of that length
+    let mut tails: Vec<usize> = vec![];
+    let mut predecessors = vec![None; sequence.len()];
+
+    for (position, value) in sequence.iter().enumerate() {
+        let value = match value {
+            Some(value) => value,
+            None => continue,
+        };
+
+        let length = tails.partition_point(|tail| sequence[*tail].unwrap() < *value);
+
+        if length > 0 {
+            predecessors[position] = Some(tails[length - 1]);
+        }
+
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+
+    let mut result = HashSet::new();
+    let mut position = tails.last().copied();
+
+    while let Some(current) = position {
+        result.insert(current);
+        position = predecessors[current];
+    }
+
+    result
+}
@@ -0,0 +1,132 @@
+use super::{Attribute, Element, Node};
+
+// https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+// https://html.spec.whatwg.org/multipage/syntax.html#raw-text-elements
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+impl<Message> Node<Message> {
+    /// Serializes the node to HTML, as it would be rendered in the browser,
+    /// for example to render the first view on a server.
+    ///
+    /// Event handlers are left out. Properties with a string, number or
+    /// boolean value are written as the attribute of the same name, so that
+    /// `value`, `checked` and `selected` show up in the markup; a `textarea`'s
+    /// `value` becomes its text.
+    pub fn to_html_string(&self) -> String {
+        let mut html = String::new();
+        write_node(self, false, &mut html);
+        html
+    }
+}
+
+fn write_node<Message>(node: &Node<Message>, raw_text: bool, html: &mut String) {
+    match node {
+        Node::Element(element) => write_element(element, html),
+        Node::Text(text) if raw_text => escape_raw_text(text, html),
+        Node::Text(text) => escape(text, false, html),
+        Node::Lazy(lazy) => write_node(lazy.node(), raw_text, html),
+    }
+}
+
+fn write_element<Message>(element: &Element<Message>, html: &mut String) {
+    let (attributes, text) = markup(element);
+
+    html.push('<');
+    html.push_str(&element.name);
+
+    for (name, value) in &attributes {
+        html.push(' ');
+        html.push_str(name);
+
+        // https://developer.mozilla.org/en-US/docs/Web/HTML/Attributes#boolean_attributes
+        if !value.is_empty() {
+            html.push_str("=\"");
+            escape(value, true, html);
+            html.push('"');
+        }
+    }
+
+    html.push('>');
+
+    if VOID_ELEMENTS.contains(&element.name.as_str()) {
+        return;
+    }
+
+    match text {
+        Some(text) => escape(&text, false, html),
+        None => {
+            let raw_text = RAW_TEXT_ELEMENTS.contains(&element.name.as_str());
+
+            for child in element.children.nodes() {
+                write_node(child, raw_text, html);
+            }
+        }
+    }
+
+    html.push_str("</");
+    html.push_str(&element.name);
+    html.push('>');
+}
+
+/// Returns the attributes `element` has in markup, in order, and the text
+/// that stands in for its children, if any.
+pub(crate) fn markup<Message>(element: &Element<Message>) -> (Vec<(&str, String)>, Option<String>) {
+    let mut attributes: Vec<(&str, String)> = vec![];
+    let mut text = None;
+
+    let mut set = |name, value: Option<String>| {
+        let position = attributes.iter().position(|(other, _)| *other == name);
+
+        match (position, value) {
+            (Some(position), Some(value)) => attributes[position].1 = value,
+            (Some(position), None) => {
+                attributes.remove(position);
+            }
+            (None, Some(value)) => attributes.push((name, value)),
+            (None, None) => {}
+        }
+    };
+
+    for attribute in &element.attributes {
+        match attribute {
+            Attribute::On(_) => {}
+            Attribute::Bool(name, value) => set(name, value.then(String::new)),
+            Attribute::Text(name, value) => set(name, Some(value.clone())),
+            Attribute::Property(name, value) if element.name == "textarea" && name == "value" => {
+                text = value.as_str().map(String::from);
+            }
+            Attribute::Property(name, value) => match value {
+                serde_json::Value::Bool(value) => set(name, value.then(String::new)),
+                serde_json::Value::Number(value) => set(name, Some(value.to_string())),
+                serde_json::Value::String(value) => set(name, Some(value.clone())),
+                _ => {}
+            },
+        }
+    }
+
+    (attributes, text)
+}
+
+fn escape(text: &str, attribute: bool, html: &mut String) {
+    for character in text.chars() {
+        match character {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if attribute => html.push_str("&quot;"),
+            character => html.push(character),
+        }
+    }
+}
+
+// Raw text ends at the first `</script` or `</style`, so text that came from
+// a user can't be allowed to close the element. `<\/` means `</` in both
+// JavaScript strings and CSS.
+fn escape_raw_text(text: &str, html: &mut String) {
+    html.push_str(&text.replace("</", "<\\/"));
+}
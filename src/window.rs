@@ -0,0 +1,119 @@
+use crate::subscription::{Listener, Sink, Subscription};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{KeyboardEvent, Window};
+
+type KeyHandler<Message> = dyn Fn(&str) -> Option<Message>;
+
+fn listen_to_window(event_type: &str, sink: Sink) -> Result<Listener, JsValue> {
+    Listener::event(&web_sys::window().unwrap(), event_type, sink)
+}
+
+pub struct Resize<Message> {
+    handler: Box<dyn Fn(f64, f64) -> Message>,
+}
+
+impl<Message> Resize<Message> {
+    /// Sends a message with the window's new inner width and height whenever
+    /// it is resized.
+    pub fn new<Handler>(handler: Handler) -> Self
+    where
+        Handler: 'static + Fn(f64, f64) -> Message,
+    {
+        Self {
+            handler: Box::new(handler),
+        }
+    }
+}
+
+impl<Message> Subscription<Message> for Resize<Message> {
+    fn id(&self) -> String {
+        "window:resize".into()
+    }
+
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue> {
+        listen_to_window("resize", sink)
+    }
+
+    fn message(&self, value: JsValue) -> Option<Message> {
+        let window = value
+            .dyn_into::<web_sys::Event>()
+            .ok()?
+            .target()?
+            .dyn_into::<Window>()
+            .ok()?;
+
+        let width = window.inner_width().ok()?.as_f64()?;
+        let height = window.inner_height().ok()?.as_f64()?;
+
+        Some((self.handler)(width, height))
+    }
+}
+
+pub struct KeyDown<Message> {
+    handler: Box<KeyHandler<Message>>,
+}
+
+impl<Message> KeyDown<Message> {
+    /// Sends a message for every key pressed anywhere in the window. The
+    /// handler receives the event's `key` and can ignore it by returning
+    /// `None`.
+    pub fn new<Handler>(handler: Handler) -> Self
+    where
+        Handler: 'static + Fn(&str) -> Option<Message>,
+    {
+        Self {
+            handler: Box::new(handler),
+        }
+    }
+}
+
+impl<Message> Subscription<Message> for KeyDown<Message> {
+    fn id(&self) -> String {
+        "window:keydown".into()
+    }
+
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue> {
+        listen_to_window("keydown", sink)
+    }
+
+    fn message(&self, value: JsValue) -> Option<Message> {
+        key_message(value, &*self.handler)
+    }
+}
+
+pub struct KeyUp<Message> {
+    handler: Box<KeyHandler<Message>>,
+}
+
+impl<Message> KeyUp<Message> {
+    /// Sends a message for every key released anywhere in the window. The
+    /// handler receives the event's `key` and can ignore it by returning
+    /// `None`.
+    pub fn new<Handler>(handler: Handler) -> Self
+    where
+        Handler: 'static + Fn(&str) -> Option<Message>,
+    {
+        Self {
+            handler: Box::new(handler),
+        }
+    }
+}
+
+impl<Message> Subscription<Message> for KeyUp<Message> {
+    fn id(&self) -> String {
+        "window:keyup".into()
+    }
+
+    fn listen(&self, sink: Sink) -> Result<Listener, JsValue> {
+        listen_to_window("keyup", sink)
+    }
+
+    fn message(&self, value: JsValue) -> Option<Message> {
+        key_message(value, &*self.handler)
+    }
+}
+
+fn key_message<Message>(value: JsValue, handler: &KeyHandler<Message>) -> Option<Message> {
+    let event = value.dyn_into::<KeyboardEvent>().ok()?;
+    handler(&event.key())
+}
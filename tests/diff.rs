@@ -0,0 +1,364 @@
+use rust_elm_architecture::{
+    virtual_dom::{diff, Node, Patch},
+    Attribute, Html,
+};
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// A stand-in for the DOM that patches can be applied to.
+#[derive(Debug, PartialEq)]
+enum Dom {
+    Element {
+        name: String,
+        attributes: BTreeMap<String, String>,
+        properties: BTreeMap<String, serde_json::Value>,
+        children: Vec<Dom>,
+    },
+    Text(String),
+}
+
+impl Dom {
+    fn create(node: &Html<()>) -> Self {
+        match node {
+            Node::Element(element) => {
+                let mut attributes = BTreeMap::new();
+                let mut properties = BTreeMap::new();
+
+                for attribute in &element.attributes {
+                    match attribute {
+                        Attribute::Bool(name, true) => {
+                            attributes.insert(name.clone(), String::new());
+                        }
+                        Attribute::Bool(name, false) => {
+                            attributes.remove(name);
+                        }
+                        Attribute::Text(name, value) => {
+                            attributes.insert(name.clone(), value.clone());
+                        }
+                        Attribute::Property(name, value) => {
+                            properties.insert(name.clone(), value.clone());
+                        }
+                        Attribute::On(_) => {}
+                    }
+                }
+
+                Dom::Element {
+                    name: element.name.clone(),
+                    attributes,
+                    properties,
+                    children: element
+                        .children
+                        .nodes()
+                        .into_iter()
+                        .map(Dom::create)
+                        .collect(),
+                }
+            }
+            Node::Text(text) => Dom::Text(text.clone()),
+            Node::Lazy(lazy) => Dom::create(lazy.node()),
+        }
+    }
+
+    fn find(&mut self, path: &[usize]) -> &mut Dom {
+        path.iter()
+            .fold(self, |node, index| &mut node.children()[*index])
+    }
+
+    fn children(&mut self) -> &mut Vec<Dom> {
+        match self {
+            Dom::Element { children, .. } => children,
+            Dom::Text(_) => panic!("text has no children"),
+        }
+    }
+
+    fn apply(&mut self, patch: &Patch<()>) {
+        match patch {
+            Patch::Replace { path, node } => *self.find(path) = Dom::create(node),
+            Patch::Insert { path, index, node } => {
+                self.find(path).children().insert(*index, Dom::create(node))
+            }
+            Patch::Remove { path, index } => {
+                self.find(path).children().remove(*index);
+            }
+            Patch::Move { path, from, to } => {
+                let children = self.find(path).children();
+                let child = children.remove(*from);
+                children.insert(*to, child);
+            }
+            Patch::SetText { path, text } => *self.find(path) = Dom::Text(text.to_string()),
+            Patch::SetAttribute { path, name, value } => {
+                if let Dom::Element { attributes, .. } = self.find(path) {
+                    attributes.insert(name.to_string(), value.to_string());
+                }
+            }
+            Patch::RemoveAttribute { path, name } => {
+                if let Dom::Element { attributes, .. } = self.find(path) {
+                    attributes.remove(*name);
+                }
+            }
+            Patch::SetProperty { path, name, value } => {
+                if let Dom::Element { properties, .. } = self.find(path) {
+                    properties.insert(name.to_string(), (*value).clone());
+                }
+            }
+            Patch::RemoveProperty { path, name } => {
+                if let Dom::Element { properties, .. } = self.find(path) {
+                    properties.remove(*name);
+                }
+            }
+            Patch::SetHandlers { .. } => {}
+        }
+    }
+}
+
+// Checks that applying the diff of `old` and `new` to the DOM of `old` gives
+// the DOM of `new`, and returns the patches.
+fn patch<'a>(old: &'a Html<()>, new: &'a Html<()>) -> Vec<Patch<'a, ()>> {
+    // created first, as the old view is rendered before the next one is diffed
+    let mut dom = Dom::create(old);
+
+    let patches = diff(old, new);
+    for patch in &patches {
+        dom.apply(patch);
+    }
+    assert_eq!(dom, Dom::create(new), "patches: {:?}", patches);
+
+    patches
+}
+
+fn list(keys: &[&str]) -> Html<()> {
+    Html::keyed(
+        "ul",
+        vec![],
+        keys.iter()
+            .map(|key| (key.to_string(), Html::li(vec![], vec![Html::text(key)])))
+            .collect(),
+    )
+}
+
+#[test]
+fn equal_views_have_no_patches() {
+    let view = || {
+        Html::div(
+            vec![Attribute::class("a"), Attribute::value("b")],
+            vec![Html::text("c"), Html::span(vec![], vec![])],
+        )
+    };
+
+    assert!(patch(&view(), &view()).is_empty());
+}
+
+#[test]
+fn text_is_updated() {
+    let old = Html::div(vec![], vec![Html::p(vec![], vec![Html::text("a")])]);
+    let new = Html::div(vec![], vec![Html::p(vec![], vec![Html::text("b")])]);
+
+    match patch(&old, &new).as_slice() {
+        [Patch::SetText { path, text: "b" }] => assert_eq!(path, &[0, 0]),
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+}
+
+#[test]
+fn elements_with_another_name_are_replaced() {
+    let old = Html::div(vec![], vec![Html::p(vec![], vec![])]);
+    let new = Html::div(vec![], vec![Html::span(vec![], vec![])]);
+
+    match patch(&old, &new).as_slice() {
+        [Patch::Replace { path, .. }] => assert_eq!(path, &[0]),
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+}
+
+#[test]
+fn only_changed_attributes_are_written() {
+    let old = Html::button(
+        vec![
+            Attribute::class("a"),
+            Attribute::id("b"),
+            Attribute::disabled(true),
+        ],
+        vec![],
+    );
+    let new = Html::button(
+        vec![
+            Attribute::class("a"),
+            Attribute::title("c"),
+            Attribute::disabled(false),
+        ],
+        vec![],
+    );
+
+    let mut patches: Vec<_> = patch(&old, &new)
+        .iter()
+        .map(|patch| match patch {
+            Patch::SetAttribute { name, value, .. } => format!("set {}={}", name, value),
+            Patch::RemoveAttribute { name, .. } => format!("remove {}", name),
+            patch => panic!("unexpected patch: {:?}", patch),
+        })
+        .collect();
+    patches.sort();
+
+    assert_eq!(patches, ["remove disabled", "remove id", "set title=c"]);
+}
+
+#[test]
+fn properties_are_diffed_against_the_previous_value() {
+    let old = Html::input(
+        vec![Attribute::value("a"), Attribute::checked(true)],
+        vec![],
+    );
+    let new = Html::input(vec![Attribute::value("b")], vec![]);
+
+    let mut patches: Vec<_> = patch(&old, &new)
+        .iter()
+        .map(|patch| match patch {
+            Patch::SetProperty { name, value, .. } => format!("set {}={}", name, value),
+            Patch::RemoveProperty { name, .. } => format!("remove {}", name),
+            patch => panic!("unexpected patch: {:?}", patch),
+        })
+        .collect();
+    patches.sort();
+
+    assert_eq!(patches, ["remove checked", r#"set value="b""#]);
+}
+
+#[test]
+fn handlers_are_replaced() {
+    let old = Html::button(vec![Attribute::on_click(())], vec![]);
+    let new = Html::button(vec![Attribute::on_click(())], vec![]);
+
+    assert!(matches!(
+        patch(&old, &new).as_slice(),
+        [Patch::SetHandlers { .. }]
+    ));
+}
+
+#[test]
+fn unkeyed_children_are_appended_and_removed_at_the_end() {
+    let short = Html::div(vec![], vec![Html::text("a")]);
+    let long = Html::div(
+        vec![],
+        vec![Html::text("a"), Html::text("b"), Html::text("c")],
+    );
+
+    match patch(&short, &long).as_slice() {
+        [Patch::Insert { index: 1, .. }, Patch::Insert { index: 2, .. }] => {}
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+
+    match patch(&long, &short).as_slice() {
+        [Patch::Remove { index: 2, .. }, Patch::Remove { index: 1, .. }] => {}
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+}
+
+#[test]
+fn keyed_child_is_inserted_without_touching_the_others() {
+    match patch(&list(&["b", "c", "d"]), &list(&["a", "b", "c", "d"])).as_slice() {
+        [Patch::Insert { path, index: 0, .. }] => assert!(path.is_empty()),
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+}
+
+#[test]
+fn keyed_children_are_moved() {
+    match patch(&list(&["a", "b", "c", "d"]), &list(&["d", "a", "b", "c"])).as_slice() {
+        [Patch::Move { from: 3, to: 0, .. }] => {}
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+
+    let (old, new) = (list(&["a", "b", "c", "d"]), list(&["d", "c", "b", "a"]));
+    let patches = patch(&old, &new);
+    assert_eq!(patches.len(), 3);
+}
+
+#[test]
+fn keyed_children_are_inserted_moved_and_removed() {
+    let cases: &[(&[&str], &[&str])] = &[
+        (&[], &["a", "b"]),
+        (&["a", "b"], &[]),
+        (&["a", "b", "c"], &["c", "x", "a"]),
+        (&["a", "b", "c", "d", "e"], &["e", "b", "y", "d", "a", "z"]),
+        (&["a", "b", "c", "d"], &["b", "d", "a", "c"]),
+        (&["a", "a", "b"], &["b", "a", "a"]),
+    ];
+
+    for (old, new) in cases {
+        patch(&list(old), &list(new));
+    }
+}
+
+#[test]
+fn long_keyed_lists_are_reordered() {
+    let keys: Vec<String> = (0..1000).map(|key| key.to_string()).collect();
+    let old_keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    let mut new_keys: Vec<&str> = old_keys.iter().rev().step_by(2).copied().collect();
+    new_keys.extend(["a", "b"]);
+
+    let (old, new) = (list(&old_keys), list(&new_keys));
+    let patches = patch(&old, &new);
+
+    let moves = patches
+        .iter()
+        .filter(|patch| matches!(patch, Patch::Move { .. }))
+        .count();
+    assert_eq!(moves, 499);
+}
+
+#[test]
+fn keyed_children_replace_unkeyed_ones() {
+    let old = Html::ul(vec![], vec![Html::text("a"), Html::text("b")]);
+    let new = list(&["c"]);
+
+    patch(&old, &new);
+    patch(&new, &old);
+}
+
+#[test]
+fn keyed_children_are_diffed() {
+    let old = list(&["a", "b"]);
+    let new = Html::keyed(
+        "ul",
+        vec![],
+        vec![
+            ("b".into(), Html::li(vec![], vec![Html::text("changed")])),
+            ("a".into(), Html::li(vec![], vec![Html::text("a")])),
+        ],
+    );
+
+    let patches = patch(&old, &new);
+    assert!(patches.iter().any(|patch| matches!(
+        patch,
+        Patch::SetText {
+            text: "changed",
+            ..
+        }
+    )));
+}
+
+static VIEW_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn counter(count: &i32) -> Html<()> {
+    VIEW_CALLS.fetch_add(1, Ordering::SeqCst);
+    Html::text(&count.to_string())
+}
+
+#[test]
+fn lazy_nodes_with_equal_arguments_are_skipped() {
+    let old = Html::div(vec![], vec![Html::lazy(counter, 1)]);
+    let same = Html::div(vec![], vec![Html::lazy(counter, 1)]);
+    let new = Html::div(vec![], vec![Html::lazy(counter, 2)]);
+
+    let calls = VIEW_CALLS.load(Ordering::SeqCst);
+    patch(&old, &same);
+    // once to create the old DOM, and not again for the diff or the new one
+    assert_eq!(VIEW_CALLS.load(Ordering::SeqCst), calls + 1);
+
+    match patch(&same, &new).as_slice() {
+        [Patch::SetText { text: "2", .. }] => {}
+        patches => panic!("unexpected patches: {:?}", patches),
+    }
+}
@@ -0,0 +1,80 @@
+use rust_elm_architecture::{Attribute, Commands, Export, Html};
+use serde::Serialize;
+use std::{env, fs, process};
+
+#[derive(Serialize)]
+struct Model {
+    page: String,
+    note: String,
+}
+
+fn init(route: &str) -> (Model, Commands<()>) {
+    let model = Model {
+        page: route.into(),
+        note: "</script>".into(),
+    };
+
+    (model, vec![])
+}
+
+fn view(model: &Model) -> Html<()> {
+    Html::h1(
+        vec![Attribute::class("title"), Attribute::on_click(())],
+        vec![Html::text(&model.page)],
+    )
+}
+
+#[test]
+fn page_embeds_the_view_and_model() {
+    let page = Export::new(init, view)
+        .root_id("root")
+        .render("/about")
+        .unwrap();
+
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains(r#"<div id="root"><h1 class="title">/about</h1></div>"#));
+    assert!(page.contains(
+        r#"<script type="application/json" id="root-model">{"page":"/about","note":"\u003c/script>"}</script>"#
+    ));
+}
+
+#[test]
+fn template_must_have_a_placeholder() {
+    let export = Export::new(init, view).template("<html></html>");
+
+    assert!(export.render("/").is_err());
+}
+
+#[test]
+fn routes_are_written_to_index_files() {
+    let directory = env::temp_dir().join(format!("export-{}", process::id()));
+
+    let paths = Export::new(init, view)
+        .template("<main>{{app}}</main>")
+        .routes(&["/", "/blog/first/"])
+        .write(&directory)
+        .unwrap();
+
+    assert_eq!(
+        paths,
+        [
+            directory.join("index.html"),
+            directory.join("blog").join("first").join("index.html"),
+        ]
+    );
+    assert!(fs::read_to_string(&paths[1])
+        .unwrap()
+        .starts_with(r#"<main><div id="app"><h1 class="title">/blog/first/</h1></div>"#));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn routes_cannot_leave_the_directory() {
+    let directory = env::temp_dir().join(format!("export-escape-{}", process::id()));
+
+    assert!(Export::new(init, view)
+        .route("/../outside")
+        .write(&directory)
+        .is_err());
+}
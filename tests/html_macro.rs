@@ -0,0 +1,127 @@
+use rust_elm_architecture::{decode, html, Attribute, Html};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Message {
+    Increment,
+    Input(String),
+    Key(String),
+}
+
+#[test]
+fn markup_expands_to_constructors() {
+    let name = String::from("world");
+    let view: Html<Message> = html! {
+        <div class="greeting" id={name.clone()}>
+            <h1>"Hello, " {name.as_str()} "!"</h1>
+            <input type="text" value={name} disabled />
+            <label for="a" data-role="note" aria-hidden="true">"a"</label>
+        </div>
+    };
+
+    assert_eq!(
+        view.to_html_string(),
+        concat!(
+            r#"<div class="greeting" id="world">"#,
+            "<h1>Hello, world!</h1>",
+            r#"<input type="text" value="world" disabled>"#,
+            r#"<label for="a" data-role="note" aria-hidden="true">a</label>"#,
+            "</div>",
+        )
+    );
+}
+
+#[test]
+fn bool_attributes_take_expressions() {
+    let view: Html<()> = html! { <button disabled={1 > 2} required /> };
+
+    assert_eq!(view.to_html_string(), "<button required></button>");
+}
+
+#[test]
+fn iterators_and_conditionals_become_children() {
+    let items = ["a", "b"];
+    let view = |count: i32| -> Html<()> {
+        html! {
+            <ul>
+                {for items.iter().map(|item| html! { <li>{*item}</li> })}
+                if count > 1 {
+                    <li>"many"</li>
+                } else if count == 1 {
+                    <li>"one"</li>
+                }
+                if let Some(last) = items.last() { <li>{format!("last: {}", last)}</li> }
+            </ul>
+        }
+    };
+
+    assert_eq!(
+        view(2).to_html_string(),
+        "<ul><li>a</li><li>b</li><li>many</li><li>last: b</li></ul>"
+    );
+    assert_eq!(
+        view(0).to_html_string(),
+        "<ul><li>a</li><li>b</li><li>last: b</li></ul>"
+    );
+}
+
+#[test]
+fn description_lists_are_allowed() {
+    let view: Html<()> = html! { <dl><dt>"Term"</dt><dd>"Definition"</dd></dl> };
+
+    assert_eq!(
+        view.to_html_string(),
+        "<dl><dt>Term</dt><dd>Definition</dd></dl>"
+    );
+
+    #[allow(deprecated)]
+    let misspelled: Html<()> = Html::d1(vec![], vec![]);
+    assert_eq!(misspelled.to_html_string(), "<dl></dl>");
+}
+
+#[test]
+fn custom_elements_are_allowed() {
+    let view: Html<()> = html! { <my-widget title="x"><span /></my-widget> };
+
+    assert_eq!(
+        view.to_html_string(),
+        r#"<my-widget title="x"><span></span></my-widget>"#
+    );
+}
+
+#[test]
+fn event_handlers_are_attached() {
+    let view: Html<Message> = html! {
+        <form onsubmit={Message::Increment}>
+            <button onclick={Message::Increment}>"+"</button>
+            <input oninput={|value: &str| Message::Input(value.into())} />
+            <input on:keydown={decode::key().map(Message::Key)} />
+        </form>
+    };
+
+    let mut events = vec![];
+    collect_events(&view, &mut events);
+
+    assert_eq!(events, ["submit", "click", "input", "keydown"]);
+}
+
+#[test]
+fn children_can_be_nodes() {
+    let child: Html<()> = Html::em(vec![Attribute::class("x")], vec![]);
+    let view: Html<()> = html! { <p>{child}</p> };
+
+    assert_eq!(view.to_html_string(), r#"<p><em class="x"></em></p>"#);
+}
+
+fn collect_events<Message>(node: &Html<Message>, events: &mut Vec<String>) {
+    if let Html::Element(element) = node {
+        for attribute in &element.attributes {
+            if let Attribute::On(event) = attribute {
+                events.push(event.name.clone());
+            }
+        }
+
+        for child in element.children.nodes() {
+            collect_events(child, events);
+        }
+    }
+}
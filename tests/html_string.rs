@@ -0,0 +1,128 @@
+use rust_elm_architecture::{decode, Attribute, Html};
+
+#[test]
+fn elements_and_text_are_serialized() {
+    let html: Html<()> = Html::div(
+        vec![Attribute::class("counter"), Attribute::id("main")],
+        vec![
+            Html::h1(vec![], vec![Html::text("Count")]),
+            Html::p(vec![], vec![Html::text("1 < 2 & 3 > 2")]),
+        ],
+    );
+
+    assert_eq!(
+        html.to_html_string(),
+        r#"<div class="counter" id="main"><h1>Count</h1><p>1 &lt; 2 &amp; 3 &gt; 2</p></div>"#
+    );
+}
+
+#[test]
+fn attribute_values_are_escaped() {
+    let html: Html<()> = Html::a(vec![Attribute::title(r#"say "hi" & <bye>"#)], vec![]);
+
+    assert_eq!(
+        html.to_html_string(),
+        r#"<a title="say &quot;hi&quot; &amp; &lt;bye&gt;"></a>"#
+    );
+}
+
+#[test]
+fn void_elements_have_no_end_tag() {
+    let html: Html<()> = Html::p(
+        vec![],
+        vec![
+            Html::text("a"),
+            Html::br(vec![], vec![]),
+            Html::img(vec![Attribute::src("b.png")], vec![]),
+        ],
+    );
+
+    assert_eq!(html.to_html_string(), r#"<p>a<br><img src="b.png"></p>"#);
+}
+
+#[test]
+fn boolean_attributes_are_written_only_when_true() {
+    let html: Html<()> = Html::div(
+        vec![],
+        vec![
+            Html::button(vec![Attribute::disabled(true)], vec![]),
+            Html::button(vec![Attribute::disabled(false)], vec![]),
+        ],
+    );
+
+    assert_eq!(
+        html.to_html_string(),
+        "<div><button disabled></button><button></button></div>"
+    );
+}
+
+#[test]
+fn properties_are_written_as_attributes() {
+    let html: Html<()> = Html::div(
+        vec![],
+        vec![
+            Html::input(
+                vec![Attribute::value("a\"b"), Attribute::checked(true)],
+                vec![],
+            ),
+            Html::option(vec![Attribute::selected(false)], vec![]),
+            Html::textarea(vec![Attribute::value("<text>")], vec![]),
+        ],
+    );
+
+    assert_eq!(
+        html.to_html_string(),
+        r#"<div><input value="a&quot;b" checked><option></option><textarea>&lt;text&gt;</textarea></div>"#
+    );
+}
+
+#[test]
+fn event_handlers_are_omitted() {
+    let html = Html::button(
+        vec![
+            Attribute::on_click(()),
+            Attribute::on("keydown", decode::succeed(())),
+        ],
+        vec![Html::text("ok")],
+    );
+
+    assert_eq!(html.to_html_string(), "<button>ok</button>");
+}
+
+#[test]
+fn script_text_is_not_escaped() {
+    let html: Html<()> = Html::script(vec![], vec![Html::text("if (a < b) {}")]);
+
+    assert_eq!(html.to_html_string(), "<script>if (a < b) {}</script>");
+}
+
+#[test]
+fn script_text_cannot_close_the_script() {
+    let html: Html<()> = Html::script(
+        vec![],
+        vec![Html::text("let name = \"</script><img src=x>\";")],
+    );
+
+    assert_eq!(
+        html.to_html_string(),
+        r#"<script>let name = "<\/script><img src=x>";</script>"#
+    );
+}
+
+fn label(text: &&str) -> Html<()> {
+    Html::span(vec![], vec![Html::text(text)])
+}
+
+#[test]
+fn keyed_and_lazy_nodes_are_serialized() {
+    let html = Html::keyed(
+        "ul",
+        vec![],
+        vec![
+            ("a".into(), Html::lazy(label, "a")),
+            ("b".into(), Html::li(vec![], vec![Html::text("b")])),
+        ],
+    );
+
+    assert_eq!(html.to_html_string(), "<ul><span>a</span><li>b</li></ul>");
+}
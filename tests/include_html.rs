@@ -0,0 +1,55 @@
+use rust_elm_architecture::{include_html, Attribute, Html};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Message {
+    Save,
+}
+
+fn card(kind: &str, id: u32, title: &str, saving: bool) -> Html<Message> {
+    include_html!("tests/templates/card.html", save = Message::Save)
+}
+
+#[test]
+fn holes_are_filled_in() {
+    assert_eq!(
+        card("wide", 7, "Lunch", false).to_html_string(),
+        concat!(
+            r#"<div class="card wide" data-id="7">"#,
+            "<h2>Lunch</h2>",
+            "<p>Fish &amp; chips, { braces }</p>",
+            r#"<input type="checkbox" checked>"#,
+            "<br>",
+            "<button>Save</button>",
+            "</div>",
+        )
+    );
+    assert_eq!(
+        card("wide", 7, "Lunch", true).to_html_string(),
+        concat!(
+            r#"<div class="card wide" data-id="7">"#,
+            "<h2>Lunch</h2>",
+            "<p>Fish &amp; chips, { braces }</p>",
+            r#"<input type="checkbox" checked>"#,
+            "<br>",
+            "<button disabled>Save</button>",
+            "</div>",
+        )
+    );
+}
+
+#[test]
+fn event_attributes_use_bindings() {
+    let view = card("", 0, "", false);
+    let button = match &view {
+        Html::Element(element) => element.children.nodes().into_iter().last().unwrap(),
+        _ => panic!("expected an element"),
+    };
+
+    match button {
+        Html::Element(element) => assert!(element
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::On(event) if event.name == "click"))),
+        _ => panic!("expected an element"),
+    }
+}
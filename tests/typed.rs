@@ -0,0 +1,76 @@
+use rust_elm_architecture::{
+    typed::{self, attribute, TypedAttribute},
+    Attribute, Html,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Message {
+    Submit,
+}
+
+#[test]
+fn typed_elements_build_the_same_nodes() {
+    let view: Html<Message> = typed::form(
+        vec![
+            attribute::id("login"),
+            attribute::method("post"),
+            attribute::on_submit(Message::Submit),
+        ],
+        vec![
+            typed::input(
+                vec![
+                    attribute::type_("checkbox"),
+                    attribute::checked(true),
+                    attribute::required(true),
+                ],
+                vec![],
+            ),
+            typed::label(
+                vec![attribute::for_("remember")],
+                vec!["Remember me".into()],
+            ),
+        ],
+    );
+
+    assert_eq!(
+        view.to_html_string(),
+        concat!(
+            r#"<form id="login" method="post">"#,
+            r#"<input type="checkbox" checked required>"#,
+            r#"<label for="remember">Remember me</label>"#,
+            "</form>",
+        )
+    );
+}
+
+#[test]
+fn form_controls_take_a_value() {
+    let textarea: Html<()> = typed::textarea(vec![attribute::value("Hello")], vec![]);
+    assert_eq!(textarea.to_html_string(), "<textarea>Hello</textarea>");
+
+    let select: Html<()> = typed::select(
+        vec![attribute::value("b")],
+        vec![typed::option(vec![attribute::value("b")], vec!["B".into()])],
+    );
+    let untyped: Html<()> = Html::select(
+        vec![Attribute::value("b")],
+        vec![Html::option(vec![Attribute::value("b")], vec!["B".into()])],
+    );
+    assert_eq!(select.to_html_string(), untyped.to_html_string());
+}
+
+#[test]
+fn typed_and_untyped_attributes_mix() {
+    let view: Html<()> = typed::div(
+        vec![
+            attribute::class("card"),
+            TypedAttribute::unchecked(Attribute::Text("data-id".into(), "7".into())),
+        ],
+        vec![Html::span(vec![Attribute::class("untyped")], vec![])],
+    );
+
+    assert_eq!(
+        view.to_html_string(),
+        r#"<div class="card" data-id="7"><span class="untyped"></span></div>"#
+    );
+}
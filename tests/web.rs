@@ -3,11 +3,604 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use async_trait::async_trait;
+use rust_elm_architecture::{
+    decode::{self, Decoder},
+    export,
+    subscription::{Listener, Sink},
+    virtual_dom::Options,
+    App, Attribute, Command, Export, Html, Subs, Subscription,
+};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
 use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn json(text: &str) -> JsValue {
+    js_sys::JSON::parse(text).unwrap()
+}
+
+// Resolves once commands and other tasks queued so far have run.
+async fn next_tick() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback(&resolve)
+            .unwrap();
+    });
+
+    JsFuture::from(promise).await.unwrap();
+}
+
+// Resolves after the next animation frame, once the messages waiting for it
+// have been applied.
+async fn next_frame() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .request_animation_frame(&resolve)
+            .unwrap();
+    });
+
+    JsFuture::from(promise).await.unwrap();
+}
+
+// Appends an element with `id` to the document for an app to mount on.
+fn root(id: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root = document.create_element("div").unwrap();
+    root.set_id(id);
+    document.body().unwrap().append_child(&root).unwrap();
+    root
+}
+
+// The first element in the document matching `selector`.
+fn find(selector: &str) -> web_sys::HtmlElement {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    document
+        .query_selector(selector)
+        .unwrap()
+        .unwrap()
+        .unchecked_into()
+}
+
+// Logs when it starts listening and when its listener is torn down.
+struct Probe {
+    id: String,
+    log: Log,
+}
+
+impl Subscription<u32> for Probe {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn listen(&self, _sink: Sink) -> Result<Listener, JsValue> {
+        self.log.borrow_mut().push(format!("start {}", self.id));
+
+        let log = self.log.clone();
+        let id = self.id.clone();
+
+        Ok(Listener::new(move || {
+            log.borrow_mut().push(format!("stop {}", id))
+        }))
+    }
+
+    fn message(&self, _value: JsValue) -> Option<u32> {
+        None
+    }
+}
+
+#[wasm_bindgen_test]
+fn subscriptions_are_started_replaced_and_dropped() {
+    root("subscriptions");
+
+    let log = Log::default();
+    let probe_log = log.clone();
+
+    // the model is the number of seconds between ticks
+    let app = App::new(
+        || (1, vec![]),
+        |seconds, model: &mut u32| {
+            *model = seconds;
+            vec![]
+        },
+        |_| Html::text(""),
+        "subscriptions",
+    )
+    .subscriptions(move |seconds| -> Subs<u32> {
+        vec![Probe {
+            id: format!("every {}s", seconds),
+            log: probe_log.clone(),
+        }
+        .boxed()]
+    })
+    .render_synchronously();
+
+    app.start();
+    assert_eq!(*log.borrow(), ["start every 1s"]);
+
+    app.handle_message(1);
+    assert_eq!(*log.borrow(), ["start every 1s"]);
+
+    app.handle_message(5);
+    assert_eq!(
+        *log.borrow(),
+        ["start every 1s", "start every 5s", "stop every 1s"]
+    );
+
+    app.stop().unwrap();
+    assert_eq!(
+        *log.borrow(),
+        [
+            "start every 1s",
+            "start every 5s",
+            "stop every 1s",
+            "stop every 5s"
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn field_decodes_a_property() {
+    let name = decode::field("name", decode::string());
+
+    assert_eq!(name.decode(&json(r#"{"name": "Ada"}"#)), Ok("Ada".into()));
+    assert_eq!(
+        name.decode(&json(r#"{"name": 1}"#)),
+        Err(r#"at field "name": expected a string, got JsValue(1)"#.into())
+    );
+    assert_eq!(
+        name.decode(&JsValue::from("Ada")),
+        Err(r#"expected an object with field "name", got JsValue("Ada")"#.into())
+    );
+}
+
+#[wasm_bindgen_test]
+fn at_decodes_a_nested_property() {
+    let value = decode::at(&["target", "value"], decode::string());
+
+    assert_eq!(
+        value.decode(&json(r#"{"target": {"value": "hi"}}"#)),
+        Ok("hi".into())
+    );
+    assert_eq!(
+        value.decode(&json(r#"{"target": {}}"#)),
+        Err(
+            r#"at field "target": at field "value": expected a string, got JsValue(undefined)"#
+                .into()
+        )
+    );
+}
+
+#[wasm_bindgen_test]
+fn one_of_succeeds_with_the_first_decoder_that_does() {
+    let text = decode::one_of(vec![
+        decode::string(),
+        decode::f64().map(|number| number.to_string()),
+    ]);
+
+    assert_eq!(text.decode(&JsValue::from("one")), Ok("one".into()));
+    assert_eq!(text.decode(&JsValue::from(1)), Ok("1".into()));
+    assert_eq!(
+        text.decode(&JsValue::TRUE),
+        Err(
+            "none of the decoders succeeded: expected a string, got JsValue(true); \
+             expected a number, got JsValue(true)"
+                .into()
+        )
+    );
+}
+
+#[wasm_bindgen_test]
+fn and_then_decodes_depending_on_a_result() {
+    let area: Decoder<f64> =
+        decode::field("shape", decode::string()).and_then(|shape| match shape.as_str() {
+            "square" => decode::field("side", decode::f64()).map(|side| side * side),
+            _ => decode::fail(&format!("unknown shape {:?}", shape)),
+        });
+
+    assert_eq!(
+        area.decode(&json(r#"{"shape": "square", "side": 3}"#)),
+        Ok(9.0)
+    );
+    assert_eq!(
+        area.decode(&json(r#"{"shape": "circle", "radius": 1}"#)),
+        Err(r#"unknown shape "circle""#.into())
+    );
+}
+
+#[wasm_bindgen_test]
+fn fail_always_fails() {
+    assert_eq!(
+        decode::fail::<()>("nope").decode(&JsValue::NULL),
+        Err("nope".into())
+    );
+}
+
+#[wasm_bindgen_test]
+fn i32_rejects_fractions() {
+    assert_eq!(decode::i32().decode(&JsValue::from(2)), Ok(2));
+    assert_eq!(
+        decode::i32().decode(&JsValue::from(1.5)),
+        Err("expected an integer, got 1.5".into())
+    );
+}
+
+#[wasm_bindgen_test]
+fn hydrating_adopts_the_markup_in_the_root() {
+    root("hydrate").set_inner_html("\n  <button>0</button>\n");
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let button: web_sys::HtmlElement = document
+        .query_selector("#hydrate button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+
+    let app = App::new(
+        || (0, vec![]),
+        |(), count: &mut u32| {
+            *count += 1;
+            vec![]
+        },
+        |count| {
+            Html::button(
+                vec![Attribute::on_click(())],
+                vec![Html::text(&count.to_string())],
+            )
+        },
+        "hydrate",
+    )
+    .hydrate()
+    .render_synchronously();
+
+    app.start();
+
+    let root = document.get_element_by_id("hydrate").unwrap();
+    assert_eq!(root.child_nodes().length(), 1);
+    assert!(root.first_child().unwrap().is_same_node(Some(&button)));
+
+    button.click();
+    assert_eq!(root.inner_html(), "<button>1</button>");
+}
+
 #[wasm_bindgen_test]
-fn pass() {
-    assert_eq!(1 + 1, 2);
+fn hydrating_skips_the_indentation_of_nested_markup() {
+    let root = root("indented");
+    root.set_inner_html("\n<ul>\n  <li>\n    <button>+</button>\n  </li>\n  <li>0</li>\n</ul>\n");
+
+    let button = find("#indented button");
+    let count = find("#indented ul").last_element_child().unwrap();
+
+    let app = App::new(
+        || (0, vec![]),
+        |(), count: &mut u32| {
+            *count += 1;
+            vec![]
+        },
+        |count| {
+            Html::ul(
+                vec![],
+                vec![
+                    Html::li(
+                        vec![],
+                        vec![Html::button(
+                            vec![Attribute::on_click(())],
+                            vec![Html::text("+")],
+                        )],
+                    ),
+                    Html::li(vec![], vec![Html::text(&count.to_string())]),
+                ],
+            )
+        },
+        "indented",
+    )
+    .hydrate()
+    .render_synchronously();
+
+    app.start();
+
+    assert!(find("#indented button").is_same_node(Some(&button)));
+    assert!(find("#indented ul")
+        .last_element_child()
+        .unwrap()
+        .is_same_node(Some(&count)));
+
+    button.click();
+    assert_eq!(
+        root.inner_html(),
+        "<ul><li><button>+</button></li><li>1</li></ul>"
+    );
+}
+
+#[wasm_bindgen_test]
+fn exported_pages_are_hydrated() {
+    let view = |name: &String| Html::p(vec![], vec![Html::text(name)]);
+    let page = Export::new(|route| (route.to_string(), vec![]), view)
+        .root_id("exported")
+        .template("{{app}}")
+        .render("/about")
+        .unwrap();
+
+    root("export").set_inner_html(&page);
+
+    let app = App::with_flags(
+        |name: String| (name, vec![]),
+        |(), _: &mut String| vec![],
+        view,
+        "exported",
+    )
+    .hydrate()
+    .render_synchronously();
+
+    app.start_with_flags(export::read_model("exported").unwrap())
+        .unwrap();
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let root = document.get_element_by_id("exported").unwrap();
+    assert_eq!(root.child_nodes().length(), 1);
+    assert_eq!(root.inner_html(), "<p>/about</p>");
+}
+
+// A command that finishes as soon as it runs.
+struct Immediately(&'static str);
+
+#[async_trait(?Send)]
+impl Command<&'static str> for Immediately {
+    async fn run(&self) -> Option<&'static str> {
+        Some(self.0)
+    }
+}
+
+#[wasm_bindgen_test]
+async fn messages_are_applied_one_at_a_time_in_order() {
+    let root = root("queue");
+
+    // the model is the messages applied so far
+    let app = App::new(
+        || (vec![], vec![]),
+        |message, applied: &mut Vec<&str>| {
+            applied.push(message);
+
+            match message {
+                "first" => vec![Immediately("command").boxed()],
+                _ => vec![],
+            }
+        },
+        |applied| {
+            Html::button(
+                vec![Attribute::on_click("click")],
+                vec![Html::text(&applied.join(", "))],
+            )
+        },
+        "queue",
+    )
+    // a click while "first" is being applied, which has to wait for it
+    .on_message(|message| {
+        if *message == "first" {
+            let document = web_sys::window().unwrap().document().unwrap();
+            let button: web_sys::HtmlElement = document
+                .query_selector("#queue button")
+                .unwrap()
+                .unwrap()
+                .unchecked_into();
+
+            button.click();
+        }
+    })
+    .render_synchronously();
+
+    app.start();
+    app.handle_message("first");
+    assert_eq!(root.text_content().unwrap(), "first, click");
+
+    next_tick().await;
+    assert_eq!(root.text_content().unwrap(), "first, click, command");
+}
+
+#[wasm_bindgen_test]
+async fn jumping_back_drops_messages_waiting_for_a_frame() {
+    let root = root("jump");
+
+    let app = App::new(
+        || (vec![], vec![]),
+        |message, applied: &mut Vec<&str>| {
+            applied.push(message);
+            vec![]
+        },
+        |applied| Html::text(&applied.join(", ")),
+        "jump",
+    )
+    .debug();
+
+    app.start();
+    app.handle_message("a");
+    next_frame().await;
+
+    app.handle_message("b");
+    app.jump_to(0);
+    next_frame().await;
+
+    assert_eq!(root.text_content().unwrap(), "");
+
+    let debugger = app.debugger().unwrap();
+    assert_eq!(debugger.paused_at(), Some(0));
+    assert_eq!(debugger.history().len(), 1);
+}
+
+#[wasm_bindgen_test]
+fn every_message_observer_is_called() {
+    root("observers");
+
+    let log = Log::default();
+    let first = log.clone();
+    let second = log.clone();
+
+    let app = App::new(
+        || ((), vec![]),
+        |_: &str, _: &mut ()| vec![],
+        |_| Html::text(""),
+        "observers",
+    )
+    .on_message(move |message| first.borrow_mut().push(format!("first {}", message)))
+    .on_message(move |message| second.borrow_mut().push(format!("second {}", message)))
+    .render_synchronously();
+
+    app.start();
+    app.handle_message("a");
+
+    assert_eq!(*log.borrow(), ["first a", "second a"]);
+}
+
+#[wasm_bindgen_test]
+fn once_handlers_stay_spent_across_renders() {
+    let root = root("once");
+
+    let app = App::new(
+        || (0, vec![]),
+        |(), clicks: &mut u32| {
+            *clicks += 1;
+            vec![]
+        },
+        |clicks| {
+            let once = Options {
+                once: true,
+                ..Options::default()
+            };
+
+            Html::button(
+                vec![Attribute::on_click(()).with_options(once)],
+                vec![Html::text(&clicks.to_string())],
+            )
+        },
+        "once",
+    )
+    .render_synchronously();
+
+    app.start();
+
+    find("#once button").click();
+    assert_eq!(root.text_content().unwrap(), "1");
+
+    find("#once button").click();
+    assert_eq!(root.text_content().unwrap(), "1");
+}
+
+// Logs the messages of an app with a `&str` message and no model.
+fn logged<View>(log: &Log, view: View, root: &'static str) -> App<(), &'static str>
+where
+    View: 'static + Fn(&()) -> Html<&'static str>,
+{
+    let log = log.clone();
+
+    App::new(|| ((), vec![]), |_, _: &mut ()| vec![], view, root)
+        .on_message(move |message| log.borrow_mut().push(message.to_string()))
+        .render_synchronously()
+}
+
+#[wasm_bindgen_test]
+fn nested_apps_only_handle_their_own_events() {
+    root("outer");
+    let log = Log::default();
+
+    let outer = logged(
+        &log,
+        |_| {
+            Html::div(
+                vec![Attribute::on_click("outer div")],
+                vec![
+                    Html::button(vec![Attribute::on_click("outer button")], vec![]),
+                    Html::div(vec![Attribute::id("inner")], vec![]),
+                ],
+            )
+        },
+        "outer",
+    );
+    outer.start();
+
+    let inner = logged(
+        &log,
+        |_| Html::button(vec![Attribute::on_click("inner button")], vec![]),
+        "inner",
+    );
+    inner.start();
+
+    find("#inner button").click();
+    assert_eq!(*log.borrow(), ["inner button", "outer div"]);
+}
+
+#[wasm_bindgen_test]
+fn capture_handlers_run_before_bubble_handlers() {
+    root("phases");
+    let log = Log::default();
+
+    let capture = Options {
+        capture: true,
+        ..Options::default()
+    };
+
+    let app = logged(
+        &log,
+        move |_| {
+            Html::div(
+                vec![
+                    Attribute::on_click("bubble div"),
+                    Attribute::on_click("capture div").with_options(capture),
+                ],
+                vec![Html::button(
+                    vec![
+                        Attribute::on_click("bubble button"),
+                        Attribute::on_click("capture button").with_options(capture),
+                    ],
+                    vec![],
+                )],
+            )
+        },
+        "phases",
+    );
+    app.start();
+
+    find("#phases button").click();
+    assert_eq!(
+        *log.borrow(),
+        [
+            "capture div",
+            "capture button",
+            "bubble button",
+            "bubble div"
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+fn stopping_propagation_skips_the_ancestors_handlers() {
+    root("stop");
+    let log = Log::default();
+
+    let app = logged(
+        &log,
+        |_| {
+            Html::div(
+                vec![Attribute::on_click("div")],
+                vec![Html::button(
+                    vec![Attribute::stop_propagation_on(
+                        "click",
+                        decode::succeed(("button", true)),
+                    )],
+                    vec![],
+                )],
+            )
+        },
+        "stop",
+    );
+    app.start();
+
+    find("#stop button").click();
+    assert_eq!(*log.borrow(), ["button"]);
 }